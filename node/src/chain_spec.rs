@@ -1,7 +1,8 @@
 use sp_core::{Pair, Public, sr25519};
 use mintcraft_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	SudoConfig, SystemConfig, CommodityConfig, ActorConfig, NatureConfig, DungeonsConfig, WASM_BINARY, Signature,
+	MaxNfts, MaxNftsPerUser,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -151,7 +152,14 @@ fn testnet_genesis(
 		}),
 		pallet_sudo: Some(SudoConfig {
 			// Assign network admin rights.
-			key: root_key,
+			key: root_key.clone(),
 		}),
+		mc_nft: Some(CommodityConfig {
+			// The equipment collection 0, owned and issued by the root key.
+			collections: vec![(root_key.clone(), root_key, MaxNfts::get(), MaxNftsPerUser::get())],
+		}),
+		mc_actor: Some(ActorConfig {}),
+		mc_nature: Some(NatureConfig {}),
+		mc_dungeons: Some(DungeonsConfig {}),
 	}
 }
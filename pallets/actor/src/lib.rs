@@ -7,22 +7,28 @@ use sp_runtime::{
 		// StaticLookup,
 		One,
 		// Zero,
-		// Saturating, CheckedSub, CheckedAdd,
+		Saturating,
+		// CheckedSub, CheckedAdd,
 	},
 };
-// use frame_support::{
+use frame_support::{
 	// ensure,
-	// traits::{ Get },
+	traits::{ Get },
+	weights::Weight,
 	// dispatch::DispatchError,
-// };
+};
 use codec::{Encode, Decode};
 use mc_support::{
-	traits::{ LifeTime, UniqueAssets }
+	primitives::{ UniqueAssetInfo },
+	traits::{ LifeTime, UniqueAssets, UniqueAssetLock, Experience, ExperienceCurve }
 };
 
+pub mod migrations;
+
 pub use pallet::*;
 
 pub type AssetIdOf<T> = <<T as Config>::UniqueAssets as UniqueAssets<<T as frame_system::Config>::AccountId>>::AssetId;
+pub type CollectionIdOf<T> = <<T as Config>::UniqueAssets as UniqueAssets<<T as frame_system::Config>::AccountId>>::CollectionId;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -47,13 +53,52 @@ pub mod pallet {
 		/// LifeTime calclator
 		type ActorLifeTime: LifeTime<Self::BlockNumber>;
 
-		/// NFT Assets
-		type UniqueAssets: UniqueAssets<Self::AccountId>;
+		/// The arithmetic type of formula identifier.
+		type FormulaId: Member + Parameter + Default + Copy;
+
+		/// NFT Assets, the category of asset info decides the equipment slot
+		type UniqueAssets: UniqueAssets<
+			Self::AccountId,
+			AssetInfo = UniqueAssetInfo<Self::FormulaId, Self::BlockNumber>,
+		>;
+
+		/// What happens to the equipments of a dead actor
+		type DeathEquipmentPolicy: Get<EquipmentPolicy>;
+
+		/// The experience required by each level
+		type ExperienceCurve: ExperienceCurve;
+
+		/// The highest level of actors
+		type MaxLevel: Get<u8>;
+
+		/// The collection of unique assets that can be equipped
+		type EquipmentCollection: Get<CollectionIdOf<Self>>;
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		// TODO handle dying actors
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() == Releases::V1 {
+				weight = weight.saturating_add(migrations::migrate_to_expiries::<T>());
+				StorageVersion::<T>::put(Releases::V2);
+			}
+			if StorageVersion::<T>::get() == Releases::V2 {
+				weight = weight.saturating_add(migrations::migrate_to_experience::<T>());
+				StorageVersion::<T>::put(Releases::V3);
+			}
+			weight
+		}
+
+		/// handle the actors whose lifespan is over
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let dying = ActorExpiries::<T>::take(now).unwrap_or_default();
+			for who in dying.iter() {
+				Self::try_die(who, now);
+			}
+			let count = dying.len() as Weight;
+			T::DbWeight::get().reads_writes(1 + count * 2, 1 + count * 3)
+		}
 	}
 
 	#[pallet::call]
@@ -69,16 +114,20 @@ pub mod pallet {
 			ensure!(!Actors::<T>::contains_key(one.clone()), Error::<T>::Alive);
 
 			let current_block = frame_system::Module::<T>::block_number();
+			let live_until = current_block + T::ActorLifeTime::base_age(1);
 			// add actor
 			Actors::<T>::insert(one.clone(), ActorInfo {
 				name: name,
 				equipments: Vec::new(),
 				born_at: current_block,
 				born_age: One::one(),
-				live_until: current_block + T::ActorLifeTime::base_age(1),
+				live_until,
 				level: 1,
 				level_progress: Percent::from_percent(0),
+				experience: 0,
 			});
+			// the expiry of this block is already handled
+			Self::insert_expiry(live_until.max(current_block + One::one()), one.clone());
 
 			// Emit event.
 			Self::deposit_event(Event::ActorBorn(one));
@@ -86,6 +135,10 @@ pub mod pallet {
 		}
 
 		/// equip some item to an actor
+		///
+		/// Only the items in `EquipmentCollection` can be equipped. The equipped item is locked
+		/// from transferring and burning. The number of equipped items in the same category is
+		/// limited by `UniqueAssetCategory::equipment_slots`.
 		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
 		pub fn equip(
 			origin: OriginFor<T>,
@@ -95,19 +148,56 @@ pub mod pallet {
 
 			Actors::<T>::try_mutate(&who, |maybe_actor| {
 				let actor = maybe_actor.as_mut().ok_or(Error::<T>::NotExist)?;
+				let current_block = frame_system::Module::<T>::block_number();
+				ensure!(actor.live_until > current_block, Error::<T>::Dead);
 
-				let item_owner = T::UniqueAssets::owner_of(&item_id);
-				ensure!(item_owner == who, Error::<T>::NotOwner);
+				let owned = T::UniqueAssets::assets_for_account(&who);
+				let (_, info) = owned.iter()
+					.find(|(id, _)| *id == item_id)
+					.ok_or(Error::<T>::NotOwner)?;
+				ensure!(!T::UniqueAssets::is_decayed(&item_id), Error::<T>::ItemDecayed);
+				ensure!(
+					T::UniqueAssets::collection_of(&item_id) == Some(T::EquipmentCollection::get()),
+					Error::<T>::NotEquipment
+				);
 
-				match actor.equipments.binary_search(&item_id) {
-					Ok(_pos) => {} // should never happen
-					Err(pos) => actor.equipments.insert(pos, item_id.clone()),
+				let pos = match actor.equipments.binary_search(&item_id) {
+					Ok(_) => return Err(Error::<T>::AlreadyEquipped.into()),
+					Err(pos) => pos,
 				};
 
+				// count the equipped items in the same slot
+				let equipped = owned.iter()
+					.filter(|(id, one)| one.category == info.category && actor.equipments.binary_search(id).is_ok())
+					.count() as u32;
+				ensure!(equipped < info.category.equipment_slots(), Error::<T>::NoFreeSlot);
+
+				actor.equipments.insert(pos, item_id.clone());
+
 				Self::deposit_event(Event::ActorEquipItem(who.clone(), item_id));
 				Ok(().into())
 			})
 		}
+
+		/// unequip some item from an actor, the item is unlocked
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub fn unequip(
+			origin: OriginFor<T>,
+			item_id: AssetIdOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			Actors::<T>::try_mutate(&who, |maybe_actor| {
+				let actor = maybe_actor.as_mut().ok_or(Error::<T>::NotExist)?;
+
+				let pos = actor.equipments.binary_search(&item_id)
+					.map_err(|_| Error::<T>::NotEquipped)?;
+				actor.equipments.remove(pos);
+
+				Self::deposit_event(Event::ActorUnequipItem(who.clone(), item_id));
+				Ok(().into())
+			})
+		}
 	}
 
 	#[pallet::storage]
@@ -119,6 +209,45 @@ pub mod pallet {
 		ActorInfo<T::BlockNumber, AssetIdOf<T>>
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn actor_expiries)]
+	/// the accounts whose actors will die at the block
+	pub(super) type ActorExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Vec<T::AccountId>
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	/// The storage layout version, used to run migrations.
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			// a new chain starts with the latest storage layout
+			StorageVersion::<T>::put(Releases::V3);
+		}
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn graveyard)]
+	/// the final stats of dead actors, by account and the block of death
+	pub type Graveyard<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::BlockNumber,
+		ActorInfo<T::BlockNumber, AssetIdOf<T>>
+	>;
+
 	#[pallet::event]
 	#[pallet::metadata(T::AccountId = "AccountId", AssetIdOf<T> = "Hash")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -129,6 +258,10 @@ pub mod pallet {
 		ActorDead(T::AccountId),
 		/// An actor equip some item
 		ActorEquipItem(T::AccountId, AssetIdOf<T>),
+		/// An actor unequip some item
+		ActorUnequipItem(T::AccountId, AssetIdOf<T>),
+		/// An actor leveled up, the lifespan was extended. \[who, level, live_until\]
+		ActorLeveledUp(T::AccountId, u8, T::BlockNumber),
 	}
 
 	#[pallet::error]
@@ -141,9 +274,43 @@ pub mod pallet {
 		Dead,
 		/// Actor doesn't own item
 		NotOwner,
+		/// The item is already equipped.
+		AlreadyEquipped,
+		/// The item is not equipped.
+		NotEquipped,
+		/// All the slots of the item category are taken.
+		NoFreeSlot,
+		/// The item is decayed.
+		ItemDecayed,
+		/// The item is not in the equipment collection.
+		NotEquipment,
 	}
 }
 
+/// What happens to the equipments of a dead actor
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum EquipmentPolicy {
+	/// The equipments are unequipped and kept by the owner
+	Unequip,
+	/// The equipments are burned with the actor
+	Bury,
+}
+
+/// The storage layout versions of this pallet.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum Releases {
+	/// The actors are never removed.
+	V1,
+	/// The actors die through `ActorExpiries`.
+	V2,
+	/// The actors gain experience.
+	V3,
+}
+
+impl Default for Releases {
+	fn default() -> Self { Self::V1 }
+}
+
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default)]
 pub struct ActorInfo<BlockNumber, Hash> {
 	/// Actor name
@@ -160,6 +327,8 @@ pub struct ActorInfo<BlockNumber, Hash> {
 	level: u8,
 	/// The progress of actor level
 	level_progress: Percent,
+	/// The experience gained in the current level
+	experience: u32,
 }
 
 // The main implementation block for the module.
@@ -167,6 +336,96 @@ impl<T: Config> Pallet<T> {
 	// Public immutables
 	/// whether the actor is alive
 	pub fn is_alive(who: &T::AccountId) -> bool {
-		Actors::<T>::contains_key(who.clone())
+		let current_block = frame_system::Module::<T>::block_number();
+		Actors::<T>::get(who).map(|actor| actor.live_until > current_block).unwrap_or(false)
+	}
+
+	// Private mutables
+
+	/// add an account to the expiry index
+	fn insert_expiry(live_until: T::BlockNumber, who: T::AccountId) {
+		ActorExpiries::<T>::mutate(live_until, |maybe_list| {
+			maybe_list.get_or_insert_with(Vec::new).push(who);
+		});
+	}
+
+	/// the actor dies if its lifespan is over, the equipments are handled by
+	/// `DeathEquipmentPolicy` and the final stats are moved to the graveyard
+	fn try_die(who: &T::AccountId, now: T::BlockNumber) {
+		let actor = match Actors::<T>::get(who) {
+			Some(actor) => actor,
+			None => return,
+		};
+		// the lifespan was extended, it is in the expiry index of the new `live_until`
+		if actor.live_until > now {
+			return;
+		}
+
+		// the equipments are unlocked once the actor is removed
+		Actors::<T>::remove(who);
+		if T::DeathEquipmentPolicy::get() == EquipmentPolicy::Bury {
+			for item_id in actor.equipments.iter() {
+				let _ = T::UniqueAssets::burn(item_id);
+			}
+		}
+		Graveyard::<T>::insert(who, now, actor);
+
+		Self::deposit_event(Event::ActorDead(who.clone()));
+	}
+}
+
+impl<T: Config> Experience<T::AccountId> for Pallet<T> {
+	/// The experience is accumulated by the living actor of `who`, every level up extends
+	/// the lifespan by `ActorLifeTime::base_age` of the new level.
+	fn award_experience(who: &T::AccountId, amount: u32) -> u32 {
+		let current_block = frame_system::Module::<T>::block_number();
+		let max_level = T::MaxLevel::get();
+
+		Actors::<T>::mutate(who, |maybe_actor| {
+			let actor = match maybe_actor.as_mut() {
+				Some(actor) if actor.live_until > current_block => actor,
+				_ => return 0,
+			};
+			if actor.level >= max_level {
+				return 0;
+			}
+
+			let mut gained = 0u32;
+			actor.experience = actor.experience.saturating_add(amount);
+			while actor.level < max_level {
+				let required = T::ExperienceCurve::required_experience(actor.level as u32);
+				if actor.experience < required {
+					break;
+				}
+				actor.experience -= required;
+				actor.level += 1;
+				actor.live_until = actor.live_until.saturating_add(T::ActorLifeTime::base_age(actor.level as u32));
+				gained += 1;
+			}
+
+			if actor.level >= max_level {
+				actor.experience = 0;
+				actor.level_progress = Percent::from_percent(0);
+			} else {
+				let required = T::ExperienceCurve::required_experience(actor.level as u32);
+				actor.level_progress = Percent::from_rational_approximation(actor.experience, required);
+			}
+
+			if gained > 0 {
+				// the old expiry is skipped as the lifespan is extended
+				Self::insert_expiry(actor.live_until, who.clone());
+				Self::deposit_event(Event::ActorLeveledUp(who.clone(), actor.level, actor.live_until));
+			}
+			gained
+		})
+	}
+}
+
+/// The equipments of living actors are locked.
+impl<T: Config> UniqueAssetLock<T::AccountId, AssetIdOf<T>> for Pallet<T> {
+	fn is_locked(owner: &T::AccountId, asset_id: &AssetIdOf<T>) -> bool {
+		Actors::<T>::get(owner)
+			.map(|actor| actor.equipments.binary_search(asset_id).is_ok())
+			.unwrap_or(false)
 	}
 }
@@ -0,0 +1,73 @@
+//! Storage migrations of the actor pallet.
+
+use super::*;
+use frame_support::{
+	Blake2_128Concat,
+	storage::migration::storage_key_iter,
+	traits::PalletInfo,
+};
+
+/// The leading fields of `ActorInfo`, which are the same in every release.
+#[derive(Decode)]
+struct ActorLifespan<BlockNumber, AssetId> {
+	_name: Vec<u8>,
+	_equipments: Vec<AssetId>,
+	_born_at: BlockNumber,
+	_born_age: BlockNumber,
+	live_until: BlockNumber,
+}
+
+/// Put every existing actor into `ActorExpiries` at the block its lifespan is over,
+/// the actors which are already over die in the next block.
+pub fn migrate_to_expiries<T: Config>() -> Weight {
+	let pallet_name = <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>()
+		.expect("the actor pallet is part of the runtime; qed");
+
+	let next_block = frame_system::Module::<T>::block_number() + One::one();
+	let actors: Vec<(T::AccountId, T::BlockNumber)> = storage_key_iter::<
+		T::AccountId,
+		ActorLifespan<T::BlockNumber, AssetIdOf<T>>,
+		Blake2_128Concat,
+	>(pallet_name.as_bytes(), b"Actors")
+		.map(|(who, actor)| (who, actor.live_until))
+		.collect();
+
+	let count = actors.len() as Weight;
+	for (who, live_until) in actors {
+		Pallet::<T>::insert_expiry(live_until.max(next_block), who);
+	}
+
+	T::DbWeight::get().reads_writes(count.saturating_mul(2), count)
+}
+
+/// The actor info before `Releases::V3`.
+#[derive(Decode)]
+struct OldActorInfo<BlockNumber, AssetId> {
+	name: Vec<u8>,
+	equipments: Vec<AssetId>,
+	born_at: BlockNumber,
+	born_age: BlockNumber,
+	live_until: BlockNumber,
+	level: u8,
+	level_progress: Percent,
+}
+
+/// Add the experience to every actor, starting from zero in the current level.
+pub fn migrate_to_experience<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+	Actors::<T>::translate::<OldActorInfo<T::BlockNumber, AssetIdOf<T>>, _>(|_, old| {
+		translated = translated.saturating_add(1);
+		Some(ActorInfo {
+			name: old.name,
+			equipments: old.equipments,
+			born_at: old.born_at,
+			born_age: old.born_age,
+			live_until: old.live_until,
+			level: old.level,
+			level_progress: old.level_progress,
+			experience: 0,
+		})
+	});
+
+	T::DbWeight::get().reads_writes(translated, translated)
+}
@@ -1,22 +1,32 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::prelude::*;
-use sp_runtime::{Percent};
-use codec::{HasCompact};
+use sp_runtime::{
+	Percent, DispatchResult,
+	traits::{Zero, Saturating},
+};
+use frame_support::ensure;
+use codec::{Encode, HasCompact};
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 use mc_support::{
 	primitives::{
-		FeatureDestinyRank, Formula, FeatureHue,
-		// UniqueAssetInfo,
+		FeatureDestinyRank, Formula, FeatureHue, FeatureRankedLevel, FeatureLevel, UniqueAssetInfo,
 	},
 	traits::{
-		ManagerAccessor, RandomNumber, FeaturedAssets, UniqueAssets,
+		ManagerAccessor, RandomNumber, FeaturedAssets, UniqueAssets, CraftingOdds,
 	},
 };
 
 pub type UniqueHash<T> = <<T as Config>::UniqueAssets as UniqueAssets<<T as frame_system::Config>::AccountId>>::AssetId;
 pub type UniqueAssetInfoOf<T> = <<T as Config>::UniqueAssets as UniqueAssets<<T as frame_system::Config>::AccountId>>::AssetInfo;
+pub type UniqueCollectionId<T> = <<T as Config>::UniqueAssets as UniqueAssets<<T as frame_system::Config>::AccountId>>::CollectionId;
 pub type AssetIdOf<T> = <<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::AssetId;
 pub type AssetBalance<T> = <<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::Balance;
 
@@ -25,7 +35,8 @@ pub mod pallet {
 	use frame_support::{
 		weights::{DispatchClass, Pays},
 		dispatch::DispatchResultWithPostInfo,
-		pallet_prelude::*
+		pallet_prelude::*,
+		transactional,
 	};
 	use frame_system::pallet_prelude::*;
 	use super::*;
@@ -52,11 +63,20 @@ pub mod pallet {
 		/// Something that provides randomness in the runtime.
 		type RandomNumber: RandomNumber<u32>;
 
+		/// The calculator of crafting success rate
+		type CraftingOdds: CraftingOdds<Self::FormulaId, AssetBalance<Self>>;
+
 		/// The featured asset module
 		type FeaturedAssets: FeaturedAssets<Self::AccountId>;
 
-		/// NFT Assets
-		type UniqueAssets: UniqueAssets<Self::AccountId>;
+		/// NFT Assets, whose info must be buildable from the executed formula
+		type UniqueAssets: UniqueAssets<
+			Self::AccountId,
+			AssetInfo = UniqueAssetInfo<Self::FormulaId, Self::BlockNumber>,
+		>;
+
+		/// The collection of unique assets minted by formulas
+		type EquipmentCollection: Get<UniqueCollectionId<Self>>;
 	}
 
 	#[pallet::hooks]
@@ -70,9 +90,7 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			formula: Formula<T::FormulaId, AssetBalance<T>>,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::FormulaManager::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			let formula_id = formula.id;
 			ensure!(!Formulas::<T>::contains_key(&formula_id), Error::<T>::IdExists);
@@ -91,9 +109,7 @@ pub mod pallet {
 			#[pallet::compact] id: T::FormulaId,
 			required_rank: FeatureDestinyRank,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::FormulaManager::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			Formulas::<T>::try_mutate(id, |maybe| {
 				let formula = maybe.as_mut().ok_or(Error::<T>::Unknown)?;
@@ -113,9 +129,7 @@ pub mod pallet {
 			minimum_elements: Vec<(FeatureHue, AssetBalance<T>)>,
 			maximum_elements: Vec<(FeatureHue, AssetBalance<T>)>,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::FormulaManager::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			Formulas::<T>::try_mutate(id, |maybe| {
 				let formula = maybe.as_mut().ok_or(Error::<T>::Unknown)?;
@@ -136,9 +150,7 @@ pub mod pallet {
 			#[pallet::compact] id: T::FormulaId,
 			rate_of_success: Percent,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::FormulaManager::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			Formulas::<T>::try_mutate(id, |maybe| {
 				let formula = maybe.as_mut().ok_or(Error::<T>::Unknown)?;
@@ -152,7 +164,12 @@ pub mod pallet {
 		}
 
 		/// execute a formula
+		///
+		/// All the assets in `use_assets` are burned. The amount of an asset is split evenly
+		/// across its hues, the remainder goes to the first hue. If the formula succeeds, a
+		/// unique asset is minted to the caller. If the minting fails, the whole call is reverted.
 		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		#[transactional]
 		pub fn excuete_formula(
 			origin: OriginFor<T>,
 			#[pallet::compact] id: T::FormulaId,
@@ -160,51 +177,61 @@ pub mod pallet {
 		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
-			Formulas::<T>::try_mutate(id, |maybe| {
-				let formula = maybe.as_mut().ok_or(Error::<T>::Unknown)?;
-
-				// let mut feature_amounts: Vec<(FeatureHue, AssetBalance<T>)> = Vec::new();
-				for (asset_id, amount) in use_assets.iter() {
-					ensure!(T::FeaturedAssets::is_in_using(*asset_id), Error::<T>::AssetNotUsed);
-					let current_asset_balance = T::FeaturedAssets::balance(*asset_id, who.clone());
-					ensure!(current_asset_balance >= *amount, Error::<T>::AssetNotEnough);
-
-					// burn all the assets
-					T::FeaturedAssets::burn(*asset_id, &who, *amount)?;
-
-					// calc feature amount
-					// let feature = T::FeaturedAssets::feature(*asset_id).unwrap();
-					// match feature.elements {
-					// 	FeatureElements::One(one) => {
-					// 	},
-					// 	FeatureElements::Two(one, two) => {
-					// 	},
-					// 	FeatureElements::Three(one, two, three) => {
-					// 	},
-					// 	FeatureElements::Four(one, two, three, four) => {
-					// 	},
-					// };
+			let formula = Formulas::<T>::get(id).ok_or(Error::<T>::Unknown)?;
+
+			// sum up the amounts of each hue
+			let mut feature_amounts: Vec<(FeatureHue, AssetBalance<T>)> = Vec::new();
+			let mut feature_levels: Vec<(FeatureRankedLevel, FeatureLevel)> = Vec::new();
+			for (asset_id, amount) in use_assets.iter() {
+				ensure!(T::FeaturedAssets::is_in_using(*asset_id), Error::<T>::AssetNotUsed);
+				let current_asset_balance = T::FeaturedAssets::balance(*asset_id, who.clone());
+				ensure!(current_asset_balance >= *amount, Error::<T>::AssetNotEnough);
+
+				// ensure the destiny rank of asset
+				let feature = T::FeaturedAssets::feature(*asset_id).ok_or(Error::<T>::AssetFeatureMissing)?;
+				let asset_rank: u8 = feature.destiny.into();
+				let required_rank: u8 = formula.required_rank.clone().into();
+				ensure!(asset_rank >= required_rank, Error::<T>::RankTooLow);
+
+				// burn all the assets
+				T::FeaturedAssets::burn(*asset_id, &who, *amount)?;
+
+				// calc feature amount, the amount is split across the hues
+				let hues = feature.elements.hues();
+				let count: AssetBalance<T> = (hues.len() as u32).into();
+				let share = *amount / count;
+				let remainder = *amount % count;
+				for (index, hue) in hues.into_iter().enumerate() {
+					let part = if index == 0 { share.saturating_add(remainder) } else { share };
+					match feature_amounts.iter_mut().find(|(one, _)| *one == hue) {
+						Some((_, total)) => *total = total.saturating_add(part),
+						None => feature_amounts.push((hue, part)),
+					}
 				}
-				// Executed
-				Self::deposit_event(Event::FormulaExecuted(id, who.clone()));
+				feature_levels.push((feature.saturation, feature.lightness));
+			}
+			Self::ensure_elements(&formula, &feature_amounts)?;
 
+			// Executed
+			Self::deposit_event(Event::FormulaExecuted(id, who.clone()));
+
+			let rate_of_success = T::CraftingOdds::success_rate(&formula, &feature_amounts, &feature_levels);
+			let rand_value = T::RandomNumber::generate_in_range_by_subject(&(id, &who).encode(), 100);
+			if rate_of_success > Percent::from_percent(rand_value as u8) {
 				// now
-				// let current_block = frame_system::Module::<T>::block_number();
-
-				// FIXME we need better generate algorithm according to feature elements
-				let rand_value = T::RandomNumber::generate_in_range(100);
-				if formula.rate_of_success > Percent::from_percent(rand_value as u8) {
-					// let hash = T::UniqueAssets::mint(&who, UniqueAssetInfo {
-					// 	formula_id: id,
-					// 	mint_at: current_block,
-					// 	name: formula.name.clone(),
-					// } as UniqueAssetInfoOf<T>)?;
-					// Self::deposit_event(Event::MintUniqueAssetSucceeded(id, who, hash));
-				} else {
-					Self::deposit_event(Event::MintUniqueAssetFailed(id, who));
-				}
-				Ok(().into())
-			})
+				let current_block = frame_system::Module::<T>::block_number();
+
+				let hash = T::UniqueAssets::mint(T::EquipmentCollection::get(), &who, UniqueAssetInfo {
+					name: formula.name,
+					formula_id: id,
+					mint_at: current_block,
+					category: formula.category,
+				})?;
+				Self::deposit_event(Event::MintUniqueAssetSucceeded(id, who, hash));
+			} else {
+				Self::deposit_event(Event::MintUniqueAssetFailed(id, who));
+			}
+			Ok(().into())
 		}
 	}
 
@@ -245,11 +272,43 @@ pub mod pallet {
 		Unknown,
 		AssetNotUsed,
 		AssetNotEnough,
+		/// The destiny rank of some asset is lower than the required rank.
+		RankTooLow,
+		/// The amount of some hue is lower than the minimum elements.
+		ElementsNotEnough,
+		/// The amount of some hue is higher than the maximum elements.
+		ElementsTooMany,
+		/// The asset in using has no feature record.
+		AssetFeatureMissing,
 	}
 }
 
 // The main implementation block for the module.
 impl<T: Config> Pallet<T> {
 	// Public immutables
-	// TODO
+
+	/// the amount of a hue in the aggregated feature amounts
+	fn amount_of_hue(
+		feature_amounts: &[(FeatureHue, AssetBalance<T>)],
+		hue: &FeatureHue,
+	) -> AssetBalance<T> {
+		feature_amounts.iter()
+			.find(|(one, _)| one == hue)
+			.map(|(_, amount)| *amount)
+			.unwrap_or_else(Zero::zero)
+	}
+
+	/// ensure the aggregated feature amounts are in the range of formula
+	fn ensure_elements(
+		formula: &Formula<T::FormulaId, AssetBalance<T>>,
+		feature_amounts: &[(FeatureHue, AssetBalance<T>)],
+	) -> DispatchResult {
+		for (hue, minimum) in formula.minimum_elements.iter() {
+			ensure!(Self::amount_of_hue(feature_amounts, hue) >= *minimum, Error::<T>::ElementsNotEnough);
+		}
+		for (hue, maximum) in formula.maximum_elements.iter() {
+			ensure!(Self::amount_of_hue(feature_amounts, hue) <= *maximum, Error::<T>::ElementsTooMany);
+		}
+		Ok(())
+	}
 }
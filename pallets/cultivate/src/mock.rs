@@ -0,0 +1,220 @@
+use crate as mc_cultivate;
+use std::cell::RefCell;
+use frame_support::{parameter_types, dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo}};
+use frame_system::{EnsureOneOf, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use mc_support::{
+	primitives::{AssetFeature, UniqueAssetInfo},
+	traits::{DesignerRole, EnsureManager, FeaturedAssets, ManagerAccessor, RandomNumber, UniqueAssets},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		Cultivate: mc_cultivate::{Module, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+/// Account 10 is the only formula designer.
+pub struct TestManagers;
+impl ManagerAccessor<u64> for TestManagers {
+	fn get_owner_id() -> u64 { 0 }
+	fn is_designer(who: &u64) -> bool { *who == 10 }
+}
+
+thread_local! {
+	static RANDOM: RefCell<u32> = RefCell::new(0);
+	static BALANCES: RefCell<Vec<((u32, u64), u64)>> = RefCell::new(Vec::new());
+	static FEATURES: RefCell<Vec<(u32, AssetFeature)>> = RefCell::new(Vec::new());
+	static MINTED: RefCell<Vec<(u32, u64, UniqueAssetInfo<u32, u64>)>> = RefCell::new(Vec::new());
+	static MINT_FULL: RefCell<bool> = RefCell::new(false);
+}
+
+/// Every number in range is `RANDOM`.
+pub struct TestRandom;
+impl RandomNumber<u32> for TestRandom {
+	fn generate_by_seed(seed: u32) -> u32 { seed }
+	fn generate_in_range_by_subject(_: &[u8], total: u32) -> u32 {
+		RANDOM.with(|v| *v.borrow() % total)
+	}
+}
+
+pub fn set_random(value: u32) {
+	RANDOM.with(|v| *v.borrow_mut() = value);
+}
+
+/// Featured assets 0 to 3 are in using, their features are set by `set_feature`.
+pub struct TestAssets;
+impl FeaturedAssets<u64> for TestAssets {
+	type AssetId = u32;
+	type Amount = u64;
+	type Balance = u64;
+
+	fn is_in_using(id: u32) -> bool { id < 4 }
+	fn total_supply(id: u32) -> u64 {
+		BALANCES.with(|v| v.borrow().iter().filter(|((one, _), _)| *one == id).map(|(_, balance)| balance).sum())
+	}
+	fn balance(id: u32, who: u64) -> u64 {
+		BALANCES.with(|v| v.borrow().iter().find(|(key, _)| *key == (id, who)).map(|(_, balance)| *balance).unwrap_or(0))
+	}
+	fn feature(id: u32) -> Option<AssetFeature> {
+		FEATURES.with(|v| v.borrow().iter().find(|(one, _)| *one == id).map(|(_, feature)| feature.clone()))
+	}
+	fn mint(id: u32, beneficiary: &u64, amount: u64) -> DispatchResultWithPostInfo {
+		let balance = Self::balance(id, *beneficiary);
+		BALANCES.with(|v| {
+			let mut list = v.borrow_mut();
+			list.retain(|(key, _)| *key != (id, *beneficiary));
+			list.push(((id, *beneficiary), balance + amount));
+		});
+		Ok(().into())
+	}
+	fn burn(id: u32, who: &u64, amount: u64) -> DispatchResultWithPostInfo {
+		let balance = Self::balance(id, *who);
+		frame_support::ensure!(balance >= amount, "balance too low");
+		BALANCES.with(|v| {
+			let mut list = v.borrow_mut();
+			list.retain(|(key, _)| *key != (id, *who));
+			list.push(((id, *who), balance - amount));
+		});
+		Ok(().into())
+	}
+	fn transfer(id: u32, origin: &u64, dest: &u64, amount: u64) -> DispatchResultWithPostInfo {
+		Self::burn(id, origin, amount)?;
+		Self::mint(id, dest, amount)
+	}
+}
+
+pub fn set_feature(id: u32, feature: AssetFeature) {
+	FEATURES.with(|v| v.borrow_mut().push((id, feature)));
+}
+
+parameter_types! {
+	pub const MaxUniqueAssets: u128 = 100;
+	pub const MaxUniqueAssetsPerUser: u64 = 10;
+	pub const EquipmentCollection: u32 = 7;
+}
+
+/// The minted unique assets are recorded, the ID of an asset is its index.
+pub struct TestUniqueAssets;
+impl UniqueAssets<u64> for TestUniqueAssets {
+	type AssetId = u64;
+	type AssetInfo = UniqueAssetInfo<u32, u64>;
+	type AssetLimit = MaxUniqueAssets;
+	type UserAssetLimit = MaxUniqueAssetsPerUser;
+	type CollectionId = u32;
+
+	fn total() -> u128 { MINTED.with(|v| v.borrow().len() as u128) }
+	fn burned() -> u128 { 0 }
+	fn total_for_account(account: &u64) -> u64 {
+		MINTED.with(|v| v.borrow().iter().filter(|(_, owner, _)| owner == account).count() as u64)
+	}
+	fn assets_for_account(account: &u64) -> Vec<(u64, Self::AssetInfo)> {
+		MINTED.with(|v| v.borrow().iter().enumerate()
+			.filter(|(_, (_, owner, _))| owner == account)
+			.map(|(index, (_, _, info))| (index as u64, info.clone()))
+			.collect())
+	}
+	fn owner_of(asset_id: &u64) -> u64 {
+		MINTED.with(|v| v.borrow().get(*asset_id as usize).map(|(_, owner, _)| *owner).unwrap_or(0))
+	}
+	fn collection_of(asset_id: &u64) -> Option<u32> {
+		MINTED.with(|v| v.borrow().get(*asset_id as usize).map(|(collection, _, _)| *collection))
+	}
+	fn total_in_collection(collection_id: u32) -> u128 {
+		MINTED.with(|v| v.borrow().iter().filter(|(one, _, _)| *one == collection_id).count() as u128)
+	}
+	fn total_for_account_in_collection(collection_id: u32, account: &u64) -> u64 {
+		MINTED.with(|v| v.borrow().iter()
+			.filter(|(one, owner, _)| *one == collection_id && owner == account)
+			.count() as u64)
+	}
+	fn assets_for_account_in_collection(collection_id: u32, account: &u64) -> Vec<(u64, Self::AssetInfo)> {
+		Self::assets_for_account(account).into_iter()
+			.filter(|(asset_id, _)| Self::collection_of(asset_id) == Some(collection_id))
+			.collect()
+	}
+	fn mint(collection_id: u32, owner_account: &u64, asset_info: Self::AssetInfo) -> Result<u64, DispatchError> {
+		if MINT_FULL.with(|v| *v.borrow()) {
+			return Err("collection is full".into());
+		}
+		MINTED.with(|v| {
+			let mut list = v.borrow_mut();
+			list.push((collection_id, *owner_account, asset_info));
+			Ok(list.len() as u64 - 1)
+		})
+	}
+	fn burn(_: &u64) -> DispatchResult { Err("not supported".into()) }
+	fn transfer(_: &u64, _: &u64) -> DispatchResult { Err("not supported".into()) }
+}
+
+pub fn minted() -> Vec<(u32, u64, UniqueAssetInfo<u32, u64>)> {
+	MINTED.with(|v| v.borrow().clone())
+}
+
+/// Make the mint of unique assets fail.
+pub fn set_mint_full(full: bool) {
+	MINT_FULL.with(|v| *v.borrow_mut() = full);
+}
+
+impl mc_cultivate::Config for Test {
+	type Event = Event;
+	type FormulaId = u32;
+	type ManagerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestManagers, u64, DesignerRole>>;
+	type FormulaManager = TestManagers;
+	type RandomNumber = TestRandom;
+	type CraftingOdds = ();
+	type FeaturedAssets = TestAssets;
+	type UniqueAssets = TestUniqueAssets;
+	type EquipmentCollection = EquipmentCollection;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap()
+		.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
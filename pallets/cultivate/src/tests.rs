@@ -0,0 +1,202 @@
+// Tests to be written here
+
+use crate::mock::*;
+use crate::*;
+use frame_support::{assert_err, assert_noop, assert_ok, error::BadOrigin, traits::Get};
+use mc_support::primitives::{AssetFeature, FeatureElements, UniqueAssetCategory};
+
+fn formula(
+	required_rank: FeatureDestinyRank,
+	minimum_elements: Vec<(FeatureHue, u64)>,
+	maximum_elements: Vec<(FeatureHue, u64)>,
+) -> Formula<u32, u64> {
+	Formula {
+		id: 1,
+		name: b"sword".to_vec(),
+		category: UniqueAssetCategory::Weapon,
+		required_rank,
+		minimum_elements,
+		maximum_elements,
+		rate_of_success: Percent::from_percent(50),
+	}
+}
+
+fn feature(destiny: FeatureDestinyRank, elements: FeatureElements) -> AssetFeature {
+	AssetFeature { destiny, elements, ..Default::default() }
+}
+
+/// asset 0 is a red Xuan asset, asset 1 is a red and blue Di asset, asset 3 has no feature
+fn setup(formula: Formula<u32, u64>) {
+	set_feature(0, feature(FeatureDestinyRank::Xuan, FeatureElements::One(FeatureHue::Red)));
+	set_feature(1, feature(FeatureDestinyRank::Di, FeatureElements::Two(FeatureHue::Red, FeatureHue::Blue)));
+	for id in 0..4 {
+		assert_ok!(TestAssets::mint(id, &1, 100));
+	}
+	assert_ok!(Cultivate::create_formula(Origin::root(), formula));
+}
+
+#[test]
+fn manager_origin_should_work() {
+	new_test_ext().execute_with(|| {
+		let one = formula(FeatureDestinyRank::Huang, vec![], vec![]);
+		// others
+		assert_noop!(Cultivate::create_formula(Origin::signed(1), one.clone()), BadOrigin);
+		assert_noop!(Cultivate::create_formula(Origin::none(), one.clone()), BadOrigin);
+		// designer
+		assert_ok!(Cultivate::create_formula(Origin::signed(10), one));
+		assert_ok!(Cultivate::modify_formula_required_rank(Origin::signed(10), 1, FeatureDestinyRank::Di));
+		assert_ok!(Cultivate::modify_formula_required_elements(
+			Origin::signed(10), 1, vec![(FeatureHue::Red, 10)], vec![]
+		));
+		assert_ok!(Cultivate::modify_formula_rate_of_success(Origin::signed(10), 1, Percent::from_percent(20)));
+		// root
+		assert_ok!(Cultivate::modify_formula_rate_of_success(Origin::root(), 1, Percent::from_percent(30)));
+		assert_eq!(Cultivate::formulas(1).unwrap().rate_of_success, Percent::from_percent(30));
+
+		assert_noop!(Cultivate::modify_formula_required_rank(Origin::signed(1), 1, FeatureDestinyRank::Xuan), BadOrigin);
+		assert_noop!(
+			Cultivate::modify_formula_required_elements(Origin::signed(1), 1, vec![], vec![]),
+			BadOrigin
+		);
+		assert_noop!(
+			Cultivate::modify_formula_rate_of_success(Origin::signed(1), 1, Percent::from_percent(100)),
+			BadOrigin
+		);
+	});
+}
+
+#[test]
+fn rank_too_low_should_fail() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Di, vec![], vec![]));
+		assert_err!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]), Error::<Test>::RankTooLow);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(1, 10)]));
+	});
+}
+
+#[test]
+fn asset_without_feature_should_fail() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![], vec![]));
+		assert_err!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(4, 10)]), Error::<Test>::AssetNotUsed);
+		assert_err!(
+			Cultivate::excuete_formula(Origin::signed(1), 1, vec![(3, 10)]),
+			Error::<Test>::AssetFeatureMissing
+		);
+		assert_err!(Cultivate::excuete_formula(Origin::signed(2), 1, vec![(0, 10)]), Error::<Test>::AssetNotEnough);
+	});
+}
+
+#[test]
+fn minimum_elements_should_be_enforced() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![(FeatureHue::Red, 10)], vec![]));
+		assert_err!(
+			Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 9)]),
+			Error::<Test>::ElementsNotEnough
+		);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]));
+	});
+}
+
+#[test]
+fn maximum_elements_should_be_enforced() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![], vec![(FeatureHue::Red, 10)]));
+		assert_err!(
+			Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 6), (0, 5)]),
+			Error::<Test>::ElementsTooMany
+		);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]));
+	});
+}
+
+#[test]
+fn amount_should_be_split_across_hues() {
+	new_test_ext().execute_with(|| {
+		// 11 of asset 1 is 6 red and 5 blue
+		setup(formula(FeatureDestinyRank::Huang, vec![(FeatureHue::Red, 7)], vec![]));
+		assert_err!(
+			Cultivate::excuete_formula(Origin::signed(1), 1, vec![(1, 11)]),
+			Error::<Test>::ElementsNotEnough
+		);
+		assert_ok!(Cultivate::modify_formula_required_elements(
+			Origin::root(),
+			1,
+			vec![(FeatureHue::Red, 6), (FeatureHue::Blue, 5)],
+			vec![(FeatureHue::Blue, 5)],
+		));
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(1, 11)]));
+	});
+}
+
+#[test]
+fn successful_roll_should_mint() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![], vec![]));
+		set_random(49);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]));
+
+		assert_eq!(TestAssets::balance(0, 1), 90);
+		assert_eq!(minted(), vec![(EquipmentCollection::get(), 1, UniqueAssetInfo {
+			name: b"sword".to_vec(),
+			formula_id: 1,
+			mint_at: 1,
+			category: UniqueAssetCategory::Weapon,
+		})]);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			crate::mock::Event::mc_cultivate(crate::Event::MintUniqueAssetSucceeded(1, 1, 0))
+		);
+	});
+}
+
+#[test]
+fn every_successful_roll_should_mint_to_executor() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![], vec![]));
+		assert_ok!(TestAssets::mint(0, &2, 100));
+		set_random(0);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]));
+		System::set_block_number(5);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(2), 1, vec![(0, 10)]));
+
+		let minted = minted();
+		assert_eq!(minted.len(), 2);
+		assert_eq!((minted[1].0, minted[1].1, minted[1].2.mint_at), (EquipmentCollection::get(), 2, 5));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			crate::mock::Event::mc_cultivate(crate::Event::MintUniqueAssetSucceeded(1, 2, 1))
+		);
+	});
+}
+
+#[test]
+fn failed_mint_should_revert_execution() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![], vec![]));
+		set_random(0);
+		set_mint_full(true);
+		let events = System::events().len();
+		assert_err!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]), "collection is full");
+		// no execution is recorded
+		assert!(minted().is_empty());
+		assert_eq!(System::events().len(), events);
+	});
+}
+
+#[test]
+fn failed_roll_should_burn_without_mint() {
+	new_test_ext().execute_with(|| {
+		setup(formula(FeatureDestinyRank::Huang, vec![], vec![]));
+		set_random(50);
+		assert_ok!(Cultivate::excuete_formula(Origin::signed(1), 1, vec![(0, 10)]));
+
+		assert_eq!(TestAssets::balance(0, 1), 90);
+		assert!(minted().is_empty());
+		assert_eq!(
+			System::events().last().unwrap().event,
+			crate::mock::Event::mc_cultivate(crate::Event::MintUniqueAssetFailed(1, 1))
+		);
+	});
+}
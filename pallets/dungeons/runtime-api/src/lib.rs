@@ -0,0 +1,24 @@
+//! Runtime API definition for the dungeons pallet.
+//!
+//! Lets the game client query the runs of a player and the leaderboard of a dungeon.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::{fmt::Debug, prelude::*};
+use mc_dungeons::{DungeonInstance, LeaderboardEntry};
+
+sp_api::decl_runtime_apis! {
+	pub trait DungeonsApi<AccountId, DungeonId, Hash, BlockNumber, Balance> where
+		AccountId: Codec + Clone + Eq,
+		DungeonId: Codec + Clone + Debug + Eq,
+		Hash: Codec,
+		BlockNumber: Codec + Clone + Eq,
+		Balance: Codec + Clone + Eq,
+	{
+		/// All the dungeon instances of a player, ordered by booking time.
+		fn player_runs(who: AccountId) -> Vec<(Hash, DungeonInstance<DungeonId, AccountId, BlockNumber, Balance>)>;
+		/// The leaderboard of a dungeon.
+		fn leaderboard(id: DungeonId) -> Vec<LeaderboardEntry<AccountId, BlockNumber, Hash>>;
+	}
+}
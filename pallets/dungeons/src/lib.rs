@@ -2,26 +2,36 @@
 
 use sp_std::{fmt::Debug, prelude::*};
 use sp_runtime::{
-	RuntimeDebug, Percent,
+	RuntimeDebug, Percent, DispatchError, DispatchResult,
 	traits::{
-		Hash, AtLeast32BitUnsigned, Zero,
-		// Saturating, CheckedSub, CheckedAdd,
+		Hash, AtLeast32BitUnsigned, Zero, One, Saturating,
+		// CheckedSub, CheckedAdd,
 	},
 };
 use frame_support::{
 	traits::{
-		Currency, ReservableCurrency,
+		Currency, ReservableCurrency, BalanceStatus, Get,
 		ExistenceRequirement::{KeepAlive},
 	},
+	weights::Weight,
+	storage::{with_transaction, TransactionOutcome},
 };
 use codec::{Encode, Decode, HasCompact, FullCodec};
 use mc_support::{
 	primitives::{ DungeonReportState },
 	traits::{
-		ManagerAccessor, FeaturedAssets, RandomNumber, RandomHash,
+		ManagerAccessor, FeaturedAssets, RandomNumber, RandomHash, Experience,
 	},
 };
 
+pub mod migrations;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 pub use pallet::*;
 
 type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -30,6 +40,22 @@ type AssetAmountPair<T> = (
 	<<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::AssetId,
 	AssetBalance<T>,
 );
+type DungeonInfoOf<T> = DungeonInfo<BalanceOf<T>, AssetAmountPair<T>>;
+pub type DungeonInstanceOf<T> = DungeonInstance<
+	<T as Config>::DungeonId,
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	BalanceOf<T>,
+>;
+pub type LeaderboardEntryOf<T> = LeaderboardEntry<
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	<T as frame_system::Config>::Hash,
+>;
+type AssetSettlementOf<T> = AssetSettlement<
+	<<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::AssetId,
+	AssetBalance<T>,
+>;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -38,6 +64,7 @@ pub mod pallet {
 		pallet_prelude::*,
 		weights::{DispatchClass, Pays},
 		dispatch::DispatchResultWithPostInfo,
+		transactional,
 	};
 	use super::*;
 
@@ -83,11 +110,71 @@ pub mod pallet {
 
 		/// percent for asset distribution
 		type AssetDistributionPercent: Get<Percent>;
+
+		/// the result of a started dungeon instance which is expired without report
+		type ExpiredReportState: Get<DungeonReportState>;
+
+		/// percent of ticket price charged when a booked ticket is cancelled
+		type TicketCancellationFee: Get<Percent>;
+
+		/// the maximum number of entries in the leaderboard of a dungeon
+		type LeaderboardSize: Get<u32>;
+
+		/// the maximum number of members in a party, including the leader
+		type MaxPartySize: Get<u32>;
+
+		/// the balance reserved by a registered dungeon server
+		type ServerBond: Get<BalanceOf<Self>>;
+
+		/// blocks for disputing after a result reported, zero to settle immediately
+		type DisputePeriod: Get<Self::BlockNumber>;
+
+		/// the balance reserved by a player raising a dispute
+		type DisputeBond: Get<BalanceOf<Self>>;
+
+		/// the actors of players gaining experience from dungeon results
+		type Experience: Experience<Self::AccountId>;
+
+		/// the experience of a perfect win, scaled by the percent of result
+		type BaseExperience: Get<u32>;
+
+		/// the maximum number of dungeon instances closed in a block, the others are delayed
+		type MaxExpiriesPerBlock: Get<u32>;
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		// TODO on finalized
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() == Releases::V1 {
+				weight = weight.saturating_add(migrations::migrate_to_instance_price::<T>());
+				StorageVersion::<T>::put(Releases::V2);
+			}
+			if StorageVersion::<T>::get() == Releases::V2 {
+				weight = weight.saturating_add(migrations::migrate_to_instance_expiries::<T>());
+				StorageVersion::<T>::put(Releases::V3);
+			}
+			weight
+		}
+
+		/// close the dungeon instances which are expired at this block
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut expired = InstanceExpiries::<T>::take(now).unwrap_or_default();
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			// the rest of expired instances are delayed to the next block
+			let limit = T::MaxExpiriesPerBlock::get() as usize;
+			if expired.len() > limit {
+				let rest = expired.split_off(limit);
+				InstanceExpiries::<T>::mutate(now.saturating_add(One::one()), |maybe_list| {
+					maybe_list.get_or_insert_with(Vec::new).extend(rest);
+				});
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			}
+			for ticket_id in expired.iter() {
+				weight = weight.saturating_add(Self::close_expired(*ticket_id, now));
+			}
+			weight
+		}
 	}
 
 	#[pallet::call]
@@ -100,9 +187,7 @@ pub mod pallet {
 			ticket_price: BalanceOf<T>,
 			provide_assets: Vec<AssetAmountPair<T>>,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			ensure!(!Dungeons::<T>::contains_key(id), Error::<T>::DungeonExists);
 			let all_asset_in_using = provide_assets.iter().all(|one| T::FeaturedAssets::is_in_using(one.0));
@@ -126,9 +211,7 @@ pub mod pallet {
 			#[pallet::compact] id: T::DungeonId,
 			ticket_price: BalanceOf<T>,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			Dungeons::<T>::try_mutate(id, |maybe_dungeon| {
 				let dungeon = maybe_dungeon.as_mut().ok_or(Error::<T>::UnknownDungeon)?;
@@ -148,9 +231,7 @@ pub mod pallet {
 			#[pallet::compact] id: T::DungeonId,
 			provide_assets: Vec<AssetAmountPair<T>>,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			Dungeons::<T>::try_mutate(id, |maybe_dungeon| {
 				let dungeon = maybe_dungeon.as_mut().ok_or(Error::<T>::UnknownDungeon)?;
@@ -169,9 +250,7 @@ pub mod pallet {
 			#[pallet::compact] id: T::DungeonId,
 			report_ranks: Vec<(DungeonReportState, Percent)>,
 		) -> DispatchResultWithPostInfo {
-			// T::ManagerOrigin::ensure_origin(origin)?;
-			let origin = ensure_signed(origin)?;
-			ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			Dungeons::<T>::try_mutate(id, |maybe_dungeon| {
 				let dungeon = maybe_dungeon.as_mut().ok_or(Error::<T>::UnknownDungeon)?;
@@ -183,6 +262,87 @@ pub mod pallet {
 			})
 		}
 
+		/// register as a dungeon server hosting the dungeons, the sender should be a server manager
+		/// reserve server bond
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn register_server(
+			origin: OriginFor<T>,
+			dungeons: Vec<T::DungeonId>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(T::AssetAdmin::is_server(&who), Error::<T>::NoPermission);
+
+			ensure!(!Servers::<T>::contains_key(&who), Error::<T>::ServerExists);
+			ensure!(dungeons.iter().all(|id| Dungeons::<T>::contains_key(id)), Error::<T>::UnknownDungeon);
+
+			// ensure server bond
+			let bond = T::ServerBond::get();
+			T::Currency::reserve(&who, bond)?;
+
+			Servers::<T>::insert(&who, ServerInfo {
+				bond,
+				dungeons,
+				running: 0,
+			});
+
+			Self::deposit_event(Event::ServerRegistered(who, bond));
+			Ok(().into())
+		}
+
+		/// modify the dungeons hosted by a server
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn modify_server_dungeons(
+			origin: OriginFor<T>,
+			dungeons: Vec<T::DungeonId>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			ensure!(dungeons.iter().all(|id| Dungeons::<T>::contains_key(id)), Error::<T>::UnknownDungeon);
+
+			Servers::<T>::try_mutate(&who, |maybe_server| {
+				let server = maybe_server.as_mut().ok_or(Error::<T>::UnknownServer)?;
+
+				server.dungeons = dungeons;
+
+				Self::deposit_event(Event::ServerDungeonsModified(who.clone()));
+				Ok(().into())
+			})
+		}
+
+		/// unregister a dungeon server without running instances
+		/// unreserve the rest of server bond
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn unregister_server(
+			origin: OriginFor<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			let server = Servers::<T>::get(&who).ok_or(Error::<T>::UnknownServer)?;
+			ensure!(server.running == 0, Error::<T>::ServerIsRunning);
+
+			T::Currency::unreserve(&who, server.bond);
+			Servers::<T>::remove(&who);
+
+			Self::deposit_event(Event::ServerUnregistered(who, server.bond));
+			Ok(().into())
+		}
+
+		/// slash the bond of a dungeon server for a disputed report
+		/// the slashed balance goes to treasury
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn slash_server(
+			origin: OriginFor<T>,
+			server_id: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			T::ManagerOrigin::ensure_origin(origin)?;
+
+			let slashed = Self::slash_bond(&server_id, amount)?;
+
+			Self::deposit_event(Event::ServerSlashed(server_id, slashed));
+			Ok(().into())
+		}
+
 		/// buy dungeon ticket
 		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
 		pub(super) fn buy_ticket(
@@ -207,47 +367,173 @@ pub mod pallet {
 				id: id,
 				player: who.clone(),
 				created_at: current_block,
+				ticket_price: dungeon.ticket_price,
 				status: DungeonInstanceStatus::Booked{ close_due: current_block + T::TicketClosingGap::get() },
 			};
 			let ticket_id = T::Hashing::hash_of(&(id.encode(), &ins.player, &ins.created_at));
 			// insert new instance
 			DungeonInstances::<T>::insert(ticket_id, ins);
+			PlayerTickets::<T>::insert(&who, ticket_id, current_block);
+			Self::insert_expiry(current_block + T::TicketClosingGap::get(), ticket_id);
 
 			Self::deposit_event(Event::DungeonTicketBought(id, who, ticket_id));
 			Ok(().into())
 		}
 
+		/// cancel a booked dungeon ticket
+		/// unreserve ticket price, the cancellation fee goes to treasury
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn cancel_ticket(
+			origin: OriginFor<T>,
+			ticket_id: T::Hash,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			DungeonInstances::<T>::try_mutate_exists(ticket_id, |maybe_instance| -> DispatchResultWithPostInfo {
+				let ins = maybe_instance.as_mut().ok_or(Error::<T>::UnknownInstance)?;
+				ensure!(ins.player == who, Error::<T>::NotTicketOwner);
+
+				// ensure current status is booked
+				let close_due = match ins.status {
+					DungeonInstanceStatus::Booked{ close_due } => close_due,
+					_ => return Err(Error::<T>::InstanceStatusShouldBeBooked.into()),
+				};
+
+				// Step.1 repatriate fee to treasury
+				let fee = T::TicketCancellationFee::get().mul_floor(ins.ticket_price);
+				if !fee.is_zero() {
+					T::Currency::repatriate_reserved(&who, &T::AssetAdmin::get_owner_id(), fee, BalanceStatus::Free)?;
+				}
+
+				// Step.2 unreserve the rest of ticket price
+				T::Currency::unreserve(&who, ins.ticket_price.saturating_sub(fee));
+
+				// Step.3 refund the other members of party
+				for member in Self::members_of(&ticket_id, ins).iter().filter(|one| **one != who) {
+					T::Currency::unreserve(member, ins.ticket_price);
+				}
+
+				// Step.4 set instance status
+				ins.status = DungeonInstanceStatus::Closed;
+				Self::remove_expiry(close_due, &ticket_id);
+
+				Self::deposit_event(Event::DungeonTicketCancelled(ins.id, who.clone(), ticket_id, fee));
+				Ok(().into())
+			})
+		}
+
+		/// book a party dungeon instance as the leader
+		/// reserve ticket price, other players can join until the party is full
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn book_party(
+			origin: OriginFor<T>,
+			#[pallet::compact] id: T::DungeonId,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(!T::AssetAdmin::is_admin(&who), Error::<T>::NoPermission);
+
+			let dungeon = Dungeons::<T>::get(id).ok_or(Error::<T>::UnknownDungeon)?;
+
+			// ensure ticket price
+			T::Currency::reserve(&who, dungeon.ticket_price)?;
+
+			// now
+			let current_block = frame_system::Module::<T>::block_number();
+
+			// build instance
+			let ins = DungeonInstance {
+				id: id,
+				player: who.clone(),
+				created_at: current_block,
+				ticket_price: dungeon.ticket_price,
+				status: DungeonInstanceStatus::Booked{ close_due: current_block + T::TicketClosingGap::get() },
+			};
+			// different from the ticket of a single player booked at the same block
+			let ticket_id = T::Hashing::hash_of(&(id.encode(), &ins.player, &ins.created_at, b"party"));
+			// insert new instance
+			DungeonInstances::<T>::insert(ticket_id, ins);
+			Parties::<T>::insert(ticket_id, sp_std::vec![who.clone()]);
+			PlayerTickets::<T>::insert(&who, ticket_id, current_block);
+			Self::insert_expiry(current_block + T::TicketClosingGap::get(), ticket_id);
+
+			Self::deposit_event(Event::DungeonPartyBooked(id, who, ticket_id));
+			Ok(().into())
+		}
+
+		/// join a booked party dungeon instance
+		/// reserve ticket price
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn join_party(
+			origin: OriginFor<T>,
+			ticket_id: T::Hash,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(!T::AssetAdmin::is_admin(&who), Error::<T>::NoPermission);
+
+			let ins = DungeonInstances::<T>::get(ticket_id).ok_or(Error::<T>::UnknownInstance)?;
+
+			// now block
+			let current_block = frame_system::Module::<T>::block_number();
+			// ensure current status is booked
+			match ins.status {
+				DungeonInstanceStatus::Booked{ close_due } => {
+					ensure!(close_due > current_block, Error::<T>::InstanceIsClosed);
+				},
+				_ => return Err(Error::<T>::InstanceStatusShouldBeBooked.into()),
+			};
+
+			Parties::<T>::try_mutate(ticket_id, |members| -> DispatchResultWithPostInfo {
+				ensure!(!members.is_empty(), Error::<T>::NotPartyInstance);
+				ensure!(!members.contains(&who), Error::<T>::AlreadyInParty);
+				ensure!((members.len() as u32) < T::MaxPartySize::get(), Error::<T>::PartyIsFull);
+
+				// ensure ticket price, the same as the leader reserved
+				T::Currency::reserve(&who, ins.ticket_price)?;
+				members.push(who.clone());
+				Ok(().into())
+			})?;
+			PlayerTickets::<T>::insert(&who, ticket_id, current_block);
+
+			Self::deposit_event(Event::DungeonPartyJoined(ins.id, who, ticket_id));
+			Ok(().into())
+		}
+
 		/// begin a dungeon instance
-		/// transfer balance, issue assets, update status
+		/// transfer balance of every member, issue assets, update status atomically
 		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		#[transactional]
 		pub(super) fn start(
 			origin: OriginFor<T>,
 			ticket_id: T::Hash,
 		) -> DispatchResultWithPostInfo {
 			let server = ensure_signed(origin)?;
-			ensure!(T::AssetAdmin::is_admin(&server), Error::<T>::NoPermission);
 
 			// ensure dungeon instance exists
 			DungeonInstances::<T>::try_mutate_exists(ticket_id, |maybe_instance| -> DispatchResultWithPostInfo {
 				let ins = maybe_instance.as_mut().ok_or(Error::<T>::UnknownInstance)?;
 				let dungeon = Dungeons::<T>::get(ins.id).ok_or(Error::<T>::UnknownDungeon)?;
+				// ensure server hosts the dungeon
+				let hosting = Servers::<T>::get(&server).map_or(false, |info| info.dungeons.contains(&ins.id));
+				ensure!(hosting, Error::<T>::NotHostingServer);
 
 				// now block
 				let current_block = frame_system::Module::<T>::block_number();
 				// ensure current status is booked
-				match ins.status {
+				let booked_close_due = match ins.status {
 					DungeonInstanceStatus::Booked{ close_due } => {
 						ensure!(close_due > current_block, Error::<T>::InstanceIsClosed);
-						// TODO 自动关闭过期的 dungeon instance
+						close_due
 					},
 					_ => return Err(Error::<T>::InstanceStatusShouldBeBooked.into()),
 				};
 
-				// Step.1 unreserve player's balance
-				T::Currency::unreserve(&ins.player, dungeon.ticket_price);
+				for member in Self::members_of(&ticket_id, ins).iter() {
+					// Step.1 unreserve player's balance
+					T::Currency::unreserve(member, ins.ticket_price);
 
-				// Step.2 transfer player's balance to server
-				let _ = T::Currency::transfer(&ins.player, &server, dungeon.ticket_price, KeepAlive)?;
+					// Step.2 transfer player's balance to server
+					let _ = T::Currency::transfer(member, &server, ins.ticket_price, KeepAlive)?;
+				}
 
 				// Step.3 server mint asset to it self.
 				for (asset_id, amount) in dungeon.provide_assets.iter() {
@@ -255,10 +541,18 @@ pub mod pallet {
 				}
 
 				// Step.4 set instance status
+				let close_due = current_block + T::TicketPlayingGap::get();
 				ins.status = DungeonInstanceStatus::Started {
 					server: server.clone(),
-					close_due: current_block + T::TicketPlayingGap::get(),
+					close_due,
 				};
+				Self::remove_expiry(booked_close_due, &ticket_id);
+				Self::insert_expiry(close_due, ticket_id);
+				Servers::<T>::mutate(&server, |maybe_server| {
+					if let Some(info) = maybe_server {
+						info.running = info.running.saturating_add(1);
+					}
+				});
 
 				// send started event
 				Self::deposit_event(Event::DungeonStarted(ins.id, ins.player.clone(), server, ticket_id));
@@ -267,14 +561,17 @@ pub mod pallet {
 		}
 
 		/// end a dungeon instance
+		/// distribute assets atomically, or wait for dispute if the dispute period is set
 		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		#[transactional]
 		pub(super) fn end(
 			origin: OriginFor<T>,
 			ticket_id: T::Hash,
 			result: DungeonReportState,
 		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
-			ensure!(T::AssetAdmin::is_admin(&who), Error::<T>::NoPermission);
+			ensure!(Servers::<T>::contains_key(&who), Error::<T>::UnknownServer);
+			ensure!(!Parties::<T>::contains_key(ticket_id), Error::<T>::IsPartyInstance);
 
 			// ensure dungeon instance exists
 			DungeonInstances::<T>::try_mutate_exists(ticket_id, |maybe_instance| -> DispatchResultWithPostInfo {
@@ -284,7 +581,7 @@ pub mod pallet {
 				// now block
 				let current_block = frame_system::Module::<T>::block_number();
 				// ensure current status is started
-				let server_id = match ins.status.clone() {
+				let (server_id, started_close_due) = match ins.status.clone() {
 					DungeonInstanceStatus::Started{
 						server,
 						close_due,
@@ -292,41 +589,193 @@ pub mod pallet {
 						// 自动关闭过期的 dungeon instance
 						ensure!(close_due > current_block, Error::<T>::InstanceIsClosed);
 						ensure!(server.clone() == who, Error::<T>::InstanceServerShouldBeSame);
-						server
+						(server, close_due)
 					},
 					_ => return Err(Error::<T>::InstanceStatusShouldBeStarted.into()),
 				};
 
-				// Step.1 get percent by result
-				let percent = match result {
-					DungeonReportState::Lose => Percent::from_percent(0),
-					DungeonReportState::PerfectWin => Percent::from_percent(100),
-					DungeonReportState::ScoredWin(score) => score,
+				Self::remove_expiry(started_close_due, &ticket_id);
+
+				let dispute_period = T::DisputePeriod::get();
+				if dispute_period.is_zero() {
+					// distribute asset to players according to result
+					Self::settle_report(ticket_id, ins, &dungeon, server_id, result, current_block)?;
+				} else {
+					// wait for the dispute of player
+					let dispute_due = current_block + dispute_period;
+					ins.status = DungeonInstanceStatus::Reported {
+						server: server_id.clone(),
+						report_at: current_block,
+						report_state: result.clone(),
+						dispute_due,
+					};
+					Self::insert_expiry(dispute_due, ticket_id);
+
+					Self::deposit_event(Event::DungeonReported(ins.id, ins.player.clone(), server_id, ticket_id, result));
+				}
+				Ok(().into())
+			})
+		}
+
+		/// dispute the reported result of a dungeon instance before the dispute period ends
+		/// reserve dispute bond, the leader disputes for a party
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn dispute(
+			origin: OriginFor<T>,
+			ticket_id: T::Hash,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			DungeonInstances::<T>::try_mutate_exists(ticket_id, |maybe_instance| -> DispatchResultWithPostInfo {
+				let ins = maybe_instance.as_mut().ok_or(Error::<T>::UnknownInstance)?;
+				ensure!(ins.player == who, Error::<T>::NotTicketOwner);
+
+				// now block
+				let current_block = frame_system::Module::<T>::block_number();
+				// ensure current status is reported
+				let (server, report_at, report_state, dispute_due) = match ins.status.clone() {
+					DungeonInstanceStatus::Reported{
+						server,
+						report_at,
+						report_state,
+						dispute_due,
+					} => {
+						ensure!(dispute_due > current_block, Error::<T>::DisputePeriodIsOver);
+						(server, report_at, report_state, dispute_due)
+					},
+					_ => return Err(Error::<T>::InstanceStatusShouldBeReported.into()),
 				};
 
-				// Step.2 distribute asset to players according to result
-				let distribute_percent = T::AssetDistributionPercent::get();
-				for (asset_id, amount) in dungeon.provide_assets.iter() {
-					let player_amount: AssetBalance<T> = distribute_percent.mul_ceil(percent.mul_ceil(*amount));
-					let treasury_amount: AssetBalance<T> = distribute_percent.mul_ceil(*amount - player_amount);
-					// FIXME 需要确保转账成功
-					if !player_amount.is_zero() {
-						T::FeaturedAssets::transfer(*asset_id, &server_id, &ins.player, player_amount)?;
-					}
-					if !treasury_amount.is_zero() {
-						T::FeaturedAssets::transfer(*asset_id, &server_id, &T::AssetAdmin::get_owner_id(), treasury_amount)?;
-					}
-				}
+				// ensure dispute bond
+				let bond = T::DisputeBond::get();
+				T::Currency::reserve(&who, bond)?;
+				DisputeBonds::<T>::insert(ticket_id, bond);
 
-				// Step.2 set instance status
-				ins.status = DungeonInstanceStatus::Ended {
-					server: server_id.clone(),
-					report_at: current_block,
-					report_state: result,
+				// wait for the resolution of manager
+				ins.status = DungeonInstanceStatus::Disputed {
+					server,
+					report_at,
+					report_state,
 				};
+				Self::remove_expiry(dispute_due, &ticket_id);
 
-				// send started event
-				Self::deposit_event(Event::DungeonEnded(ins.id, ins.player.clone(), server_id, ticket_id, percent));
+				Self::deposit_event(Event::DungeonDisputed(ins.id, who.clone(), ticket_id, bond));
+				Ok(().into())
+			})
+		}
+
+		/// resolve a disputed dungeon instance by upholding or overriding the reported result
+		/// the bond of losing side is slashed to treasury, then distribute assets atomically
+		/// an overriding result applies to every member of a party
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		#[transactional]
+		pub(super) fn resolve_dispute(
+			origin: OriginFor<T>,
+			ticket_id: T::Hash,
+			override_state: Option<DungeonReportState>,
+		) -> DispatchResultWithPostInfo {
+			T::ManagerOrigin::ensure_origin(origin)?;
+
+			DungeonInstances::<T>::try_mutate_exists(ticket_id, |maybe_instance| -> DispatchResultWithPostInfo {
+				let ins = maybe_instance.as_mut().ok_or(Error::<T>::UnknownInstance)?;
+				let dungeon = Dungeons::<T>::get(ins.id).ok_or(Error::<T>::UnknownDungeon)?;
+
+				// ensure current status is disputed
+				let (server, report_at, report_state) = match ins.status.clone() {
+					DungeonInstanceStatus::Disputed{
+						server,
+						report_at,
+						report_state,
+					} => (server, report_at, report_state),
+					_ => return Err(Error::<T>::InstanceStatusShouldBeDisputed.into()),
+				};
+
+				let bond = DisputeBonds::<T>::take(ticket_id);
+				let (upheld, result) = match override_state {
+					Some(state) if state != report_state => {
+						// the server lost, return player's bond
+						T::Currency::unreserve(&ins.player, bond);
+						Self::slash_bond(&server, bond)?;
+						(false, state)
+					},
+					_ => {
+						// the player lost
+						T::Currency::repatriate_reserved(&ins.player, &T::AssetAdmin::get_owner_id(), bond, BalanceStatus::Free)?;
+						(true, report_state)
+					},
+				};
+
+				Self::settle_reported(ticket_id, ins, &dungeon, server, result, !upheld, report_at)?;
+
+				Self::deposit_event(Event::DungeonDisputeResolved(ticket_id, upheld));
+				Ok(().into())
+			})
+		}
+
+		/// end a party dungeon instance with the result of every member
+		/// the provided assets are equally shared by members, distribute atomically,
+		/// or wait for dispute of the leader if the dispute period is set
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		#[transactional]
+		pub(super) fn end_party(
+			origin: OriginFor<T>,
+			ticket_id: T::Hash,
+			results: Vec<(T::AccountId, DungeonReportState)>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(Servers::<T>::contains_key(&who), Error::<T>::UnknownServer);
+
+			let members = Parties::<T>::get(ticket_id);
+			ensure!(!members.is_empty(), Error::<T>::NotPartyInstance);
+			// ensure every member is reported exactly once
+			let all_reported = results.len() == members.len() && members.iter()
+				.all(|member| results.iter().filter(|(one, _)| one == member).count() == 1);
+			ensure!(all_reported, Error::<T>::PartyReportsMismatch);
+
+			// ensure dungeon instance exists
+			DungeonInstances::<T>::try_mutate_exists(ticket_id, |maybe_instance| -> DispatchResultWithPostInfo {
+				let ins = maybe_instance.as_mut().ok_or(Error::<T>::UnknownInstance)?;
+				let dungeon = Dungeons::<T>::get(ins.id).ok_or(Error::<T>::UnknownDungeon)?;
+
+				// now block
+				let current_block = frame_system::Module::<T>::block_number();
+				// ensure current status is started
+				let (server_id, started_close_due) = match ins.status.clone() {
+					DungeonInstanceStatus::Started{
+						server,
+						close_due,
+					} => {
+						ensure!(close_due > current_block, Error::<T>::InstanceIsClosed);
+						ensure!(server.clone() == who, Error::<T>::InstanceServerShouldBeSame);
+						(server, close_due)
+					},
+					_ => return Err(Error::<T>::InstanceStatusShouldBeStarted.into()),
+				};
+
+				Self::remove_expiry(started_close_due, &ticket_id);
+
+				let dispute_period = T::DisputePeriod::get();
+				if dispute_period.is_zero() {
+					// distribute the shares of asset to members according to results
+					Self::settle_party_report(ticket_id, ins, &dungeon, server_id, results, current_block)?;
+				} else {
+					// wait for the dispute of leader, the result of leader is the reported state
+					let leader_result = results.iter()
+						.find(|(member, _)| *member == ins.player)
+						.map(|(_, result)| result.clone())
+						.ok_or(Error::<T>::PartyReportsMismatch)?;
+					let dispute_due = current_block + dispute_period;
+					ins.status = DungeonInstanceStatus::Reported {
+						server: server_id.clone(),
+						report_at: current_block,
+						report_state: leader_result.clone(),
+						dispute_due,
+					};
+					PartyReports::<T>::insert(ticket_id, results);
+					Self::insert_expiry(dispute_due, ticket_id);
+
+					Self::deposit_event(Event::DungeonReported(ins.id, ins.player.clone(), server_id, ticket_id, leader_result));
+				}
 				Ok(().into())
 			})
 		}
@@ -349,9 +798,101 @@ pub mod pallet {
 		_,
 		Blake2_128Concat,
 		T::Hash,
-		DungeonInstance<T::DungeonId, T::AccountId, T::BlockNumber>
+		DungeonInstance<T::DungeonId, T::AccountId, T::BlockNumber, BalanceOf<T>>
+	>;
+
+	#[pallet::storage]
+	/// the ticket ids of all the dungeon instances booked by a player
+	pub(super) type PlayerTickets<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::Hash,
+		T::BlockNumber
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn servers)]
+	/// registered dungeon servers
+	pub(super) type Servers<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		ServerInfo<BalanceOf<T>, T::DungeonId>
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn dispute_bonds)]
+	/// the bond reserved by the player of a disputed dungeon instance
+	pub(super) type DisputeBonds<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::Hash,
+		BalanceOf<T>,
+		ValueQuery
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn party_reports)]
+	/// the reported result of every member of a party dungeon instance waiting for settlement
+	pub(super) type PartyReports<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::Hash,
+		Vec<(T::AccountId, DungeonReportState)>,
+		ValueQuery
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn parties)]
+	/// the members of a party dungeon instance, the leader is the first
+	pub(super) type Parties<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::Hash,
+		Vec<T::AccountId>,
+		ValueQuery
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn leaderboards)]
+	/// the best results of a dungeon, at most `LeaderboardSize` entries
+	pub(super) type Leaderboards<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::DungeonId,
+		Vec<LeaderboardEntryOf<T>>,
+		ValueQuery
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn instance_expiries)]
+	/// the ticket ids of dungeon instances which will be expired at the block
+	pub(super) type InstanceExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Vec<T::Hash>
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	/// The storage layout version, used to run migrations.
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			// a new chain starts with the latest storage layout
+			StorageVersion::<T>::put(Releases::V3);
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::metadata(T::AccountId = "AccountId", T::Balance = "Balance", T::DungeonId = "DungeonId")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -370,6 +911,34 @@ pub mod pallet {
 		DungeonStarted(T::DungeonId, T::AccountId, T::AccountId, T::Hash),
 		/// a dungeon ended. \[dungeon_id, player_id, server_id, ticket_id, score\]
 		DungeonEnded(T::DungeonId, T::AccountId, T::AccountId, T::Hash, Percent),
+		/// the provided assets of a dungeon instance were settled. \[ticket_id, player_id, settlements\]
+		DungeonSettled(T::Hash, T::AccountId, Vec<AssetSettlementOf<T>>),
+		/// a booked ticket was cancelled. \[dungeon_id, player_id, ticket_id, fee\]
+		DungeonTicketCancelled(T::DungeonId, T::AccountId, T::Hash, BalanceOf<T>),
+		/// a booked ticket expired and was refunded. \[dungeon_id, player_id, ticket_id\]
+		DungeonTicketExpired(T::DungeonId, T::AccountId, T::Hash),
+		/// a started dungeon expired and was closed with default result. \[dungeon_id, player_id, server_id, ticket_id, score\]
+		DungeonExpired(T::DungeonId, T::AccountId, T::AccountId, T::Hash, Percent),
+		/// a party dungeon instance booked by the leader. \[dungeon_id, leader_id, ticket_id\]
+		DungeonPartyBooked(T::DungeonId, T::AccountId, T::Hash),
+		/// a player joined a party dungeon instance. \[dungeon_id, player_id, ticket_id\]
+		DungeonPartyJoined(T::DungeonId, T::AccountId, T::Hash),
+		/// a party dungeon ended, the result of each member is in `DungeonEnded`. \[dungeon_id, leader_id, server_id, ticket_id\]
+		DungeonPartyEnded(T::DungeonId, T::AccountId, T::AccountId, T::Hash),
+		/// a dungeon server registered with bond. \[server_id, bond\]
+		ServerRegistered(T::AccountId, BalanceOf<T>),
+		/// the hosted dungeons of a server were modified. \[server_id\]
+		ServerDungeonsModified(T::AccountId),
+		/// a dungeon server unregistered and its bond was returned. \[server_id, bond\]
+		ServerUnregistered(T::AccountId, BalanceOf<T>),
+		/// the bond of a dungeon server was slashed to treasury. \[server_id, amount\]
+		ServerSlashed(T::AccountId, BalanceOf<T>),
+		/// a dungeon result was reported and waits for dispute. \[dungeon_id, player_id, server_id, ticket_id, report_state\]
+		DungeonReported(T::DungeonId, T::AccountId, T::AccountId, T::Hash, DungeonReportState),
+		/// a reported result was disputed by player. \[dungeon_id, player_id, ticket_id, bond\]
+		DungeonDisputed(T::DungeonId, T::AccountId, T::Hash, BalanceOf<T>),
+		/// a dispute was resolved, the reported result was upheld or not. \[ticket_id, upheld\]
+		DungeonDisputeResolved(T::Hash, bool),
 	}
 
 	#[pallet::error]
@@ -383,9 +952,37 @@ pub mod pallet {
 		InstanceStatusShouldBeBooked,
 		InstanceStatusShouldBeStarted,
 		InstanceServerShouldBeSame,
+		NotTicketOwner,
+		NotPartyInstance,
+		IsPartyInstance,
+		AlreadyInParty,
+		PartyIsFull,
+		PartyReportsMismatch,
+		ServerExists,
+		UnknownServer,
+		ServerIsRunning,
+		NotHostingServer,
+		InstanceStatusShouldBeReported,
+		InstanceStatusShouldBeDisputed,
+		DisputePeriodIsOver,
 	}
 }
 
+/// The storage layout versions of this pallet.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum Releases {
+	/// The dungeon instances pay the current ticket price of dungeon.
+	V1,
+	/// The dungeon instances keep the ticket price reserved at booking.
+	V2,
+	/// The open dungeon instances are closed through `InstanceExpiries`.
+	V3,
+}
+
+impl Default for Releases {
+	fn default() -> Self { Self::V1 }
+}
+
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default)]
 pub struct DungeonInfo<
 	Balance: Encode + Decode + Clone + Debug + Eq + PartialEq,
@@ -397,6 +994,81 @@ pub struct DungeonInfo<
 	report_ranks: Vec<(DungeonReportState, Percent)>,
 }
 
+impl<
+	Balance: Encode + Decode + Clone + Debug + Eq + PartialEq,
+	AssetAmountPair,
+> DungeonInfo<Balance, AssetAmountPair> {
+	/// The player's percent of provided assets in the report ranks, `None` if no ranks set.
+	///
+	/// `Lose` and `PerfectWin` use their own rank, or 0% and 100% if missing.
+	/// `ScoredWin` is interpolated between the scored ranks, bounded by `Lose` at 0% score
+	/// and `PerfectWin` at 100% score.
+	pub fn rank_percent(&self, state: &DungeonReportState) -> Option<Percent> {
+		if self.report_ranks.is_empty() {
+			return None;
+		}
+		let find_rank = |target: &DungeonReportState| self.report_ranks.iter()
+			.find(|(one, _)| one == target)
+			.map(|(_, percent)| *percent);
+		let lose = find_rank(&DungeonReportState::Lose).unwrap_or_else(|| Percent::from_percent(0));
+		let perfect = find_rank(&DungeonReportState::PerfectWin).unwrap_or_else(|| Percent::from_percent(100));
+
+		let score = match state {
+			DungeonReportState::Lose => return Some(lose),
+			DungeonReportState::PerfectWin => return Some(perfect),
+			DungeonReportState::ScoredWin(score) => score.deconstruct() as u32,
+		};
+
+		// (score, percent) points sorted by score
+		let mut points: Vec<(u32, u32)> = self.report_ranks.iter()
+			.filter_map(|(one, percent)| match one {
+				DungeonReportState::ScoredWin(at) => Some((at.deconstruct() as u32, percent.deconstruct() as u32)),
+				_ => None,
+			})
+			.collect();
+		points.push((0, lose.deconstruct() as u32));
+		points.push((100, perfect.deconstruct() as u32));
+		points.sort();
+
+		let upper = points.iter().position(|(at, _)| *at >= score).unwrap_or(points.len() - 1);
+		let (to_score, to_percent) = points[upper];
+		if upper == 0 || to_score == score {
+			return Some(Percent::from_percent(to_percent as u8));
+		}
+		let (from_score, from_percent) = points[upper - 1];
+		let percent = if to_percent >= from_percent {
+			from_percent + (to_percent - from_percent) * (score - from_score) / (to_score - from_score)
+		} else {
+			from_percent - (from_percent - to_percent) * (score - from_score) / (to_score - from_score)
+		};
+		Some(Percent::from_percent(percent as u8))
+	}
+}
+
+/// The settlement of a provided asset when a dungeon instance is ended
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct AssetSettlement<AssetId, Balance> {
+	/// the id of asset
+	pub asset_id: AssetId,
+	/// the amount received by player
+	pub player: Balance,
+	/// the amount received by treasury
+	pub treasury: Balance,
+	/// the amount burned from server
+	pub burned: Balance,
+}
+
+/// The info of a registered dungeon server
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct ServerInfo<Balance, DungeonId> {
+	/// the reserved bond of server
+	pub bond: Balance,
+	/// the dungeons hosted by server
+	pub dungeons: Vec<DungeonId>,
+	/// the number of started instances which are not settled
+	pub running: u32,
+}
+
 /// The status of a dungeon instance
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
 pub enum DungeonInstanceStatus<AccountId, BlockNumber> {
@@ -412,6 +1084,22 @@ pub enum DungeonInstanceStatus<AccountId, BlockNumber> {
 		report_at: BlockNumber,
 		report_state: DungeonReportState,
 	},
+	Reported {
+		server: AccountId,
+		report_at: BlockNumber,
+		report_state: DungeonReportState,
+		dispute_due: BlockNumber,
+	},
+	Disputed {
+		server: AccountId,
+		report_at: BlockNumber,
+		report_state: DungeonReportState,
+	},
+	PartyEnded {
+		server: AccountId,
+		report_at: BlockNumber,
+		report_states: Vec<(AccountId, DungeonReportState)>,
+	},
 	Closed,
 }
 
@@ -421,16 +1109,384 @@ pub struct DungeonInstance<
 	DungeonId: Encode + Decode + Clone + Debug + Eq + PartialEq,
 	AccountId: Encode + Decode + Clone + Eq + PartialEq,
 	BlockNumber: Encode + Decode + Clone + Eq + PartialEq,
+	Balance: Encode + Decode + Clone + Eq + PartialEq,
 > {
 	/// the id of dungeon
 	id: DungeonId,
 	player: AccountId,
 	created_at: BlockNumber,
+	/// the ticket price reserved by every member at booking
+	ticket_price: Balance,
 	status: DungeonInstanceStatus<AccountId, BlockNumber>,
 }
 
+/// An entry of dungeon leaderboard
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct LeaderboardEntry<AccountId, BlockNumber, Hash> {
+	/// the player of the run
+	pub player: AccountId,
+	/// the ticket id of the run
+	pub ticket_id: Hash,
+	/// the reported result
+	pub report_state: DungeonReportState,
+	/// the block of report
+	pub report_at: BlockNumber,
+}
+
 // The main implementation block for the module.
 impl<T: Config> Pallet<T> {
 	// Public immutables
-	// TODO
+
+	/// all the dungeon instances of a player, ordered by booking time
+	pub fn player_runs(who: &T::AccountId) -> Vec<(T::Hash, DungeonInstanceOf<T>)> {
+		let mut runs: Vec<(T::Hash, DungeonInstanceOf<T>)> = PlayerTickets::<T>::iter_prefix(who)
+			.filter_map(|(ticket_id, _)| DungeonInstances::<T>::get(ticket_id).map(|ins| (ticket_id, ins)))
+			.collect();
+		runs.sort_by(|a, b| a.1.created_at.cmp(&b.1.created_at));
+		runs
+	}
+
+	/// the rank of a report in leaderboard, `PerfectWin` is above all `ScoredWin`
+	fn leaderboard_rank(state: &DungeonReportState) -> Option<u16> {
+		match state {
+			DungeonReportState::Lose => None,
+			DungeonReportState::PerfectWin => Some(101),
+			DungeonReportState::ScoredWin(score) => Some(score.deconstruct() as u16),
+		}
+	}
+
+	/// put a reported result into the leaderboard of dungeon
+	fn update_leaderboard(
+		id: T::DungeonId,
+		player: &T::AccountId,
+		ticket_id: T::Hash,
+		report_state: &DungeonReportState,
+		report_at: T::BlockNumber,
+	) {
+		let rank = match Self::leaderboard_rank(report_state) {
+			Some(rank) => rank,
+			None => return,
+		};
+		Leaderboards::<T>::mutate(id, |board| {
+			// the earlier report stays ahead of the same rank
+			let pos = board.iter()
+				.position(|one| Self::leaderboard_rank(&one.report_state).map_or(true, |one_rank| one_rank < rank))
+				.unwrap_or(board.len());
+			board.insert(pos, LeaderboardEntry {
+				player: player.clone(),
+				ticket_id,
+				report_state: report_state.clone(),
+				report_at,
+			});
+			board.truncate(T::LeaderboardSize::get() as usize);
+		});
+	}
+
+	/// the players of a dungeon instance, all the members if it is a party
+	fn members_of(ticket_id: &T::Hash, ins: &DungeonInstanceOf<T>) -> Vec<T::AccountId> {
+		let members = Parties::<T>::get(ticket_id);
+		if members.is_empty() {
+			sp_std::vec![ins.player.clone()]
+		} else {
+			members
+		}
+	}
+
+	/// slash the bond of a server to treasury, no more than the bond
+	fn slash_bond(server_id: &T::AccountId, amount: BalanceOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+		Servers::<T>::try_mutate(server_id, |maybe_server| {
+			let server = maybe_server.as_mut().ok_or(Error::<T>::UnknownServer)?;
+
+			let amount = amount.min(server.bond);
+			let remaining = T::Currency::repatriate_reserved(server_id, &T::AssetAdmin::get_owner_id(), amount, BalanceStatus::Free)?;
+			let slashed = amount.saturating_sub(remaining);
+			server.bond = server.bond.saturating_sub(slashed);
+			Ok(slashed)
+		})
+	}
+
+	/// a started instance of server was settled
+	fn release_server(server: &T::AccountId) {
+		Servers::<T>::mutate(server, |maybe_server| {
+			if let Some(info) = maybe_server {
+				info.running = info.running.saturating_sub(1);
+			}
+		});
+	}
+
+	/// add a ticket to the expiry index
+	fn insert_expiry(close_due: T::BlockNumber, ticket_id: T::Hash) {
+		InstanceExpiries::<T>::mutate(close_due, |maybe_list| {
+			maybe_list.get_or_insert_with(Vec::new).push(ticket_id);
+		});
+	}
+
+	/// remove a ticket from the expiry index
+	fn remove_expiry(close_due: T::BlockNumber, ticket_id: &T::Hash) {
+		InstanceExpiries::<T>::mutate_exists(close_due, |maybe_list| {
+			if let Some(list) = maybe_list {
+				list.retain(|one| one != ticket_id);
+				if list.is_empty() {
+					*maybe_list = None;
+				}
+			}
+		});
+	}
+
+	/// distribute the provided assets from server to player according to result
+	///
+	/// The rest of provided assets which are neither for player nor for treasury are burned
+	/// from the server. Should be called in a storage transaction.
+	fn distribute(
+		dungeon: &DungeonInfoOf<T>,
+		provide_assets: &[AssetAmountPair<T>],
+		server: &T::AccountId,
+		player: &T::AccountId,
+		result: &DungeonReportState,
+	) -> Result<(Percent, Vec<AssetSettlementOf<T>>), DispatchError> {
+		// Step.1 get percent by result
+		let percent = match result {
+			DungeonReportState::Lose => Percent::from_percent(0),
+			DungeonReportState::PerfectWin => Percent::from_percent(100),
+			DungeonReportState::ScoredWin(score) => *score,
+		};
+
+		// Step.2 distribute asset to players according to result,
+		// the report ranks of dungeon is preferred to the global distribution percent
+		let distribute_percent = T::AssetDistributionPercent::get();
+		let rank_percent = dungeon.rank_percent(result);
+		let treasury = T::AssetAdmin::get_owner_id();
+		let mut settlements = Vec::with_capacity(provide_assets.len());
+		for (asset_id, amount) in provide_assets.iter() {
+			let player_amount: AssetBalance<T> = match rank_percent {
+				Some(rank_percent) => rank_percent.mul_ceil(*amount),
+				None => distribute_percent.mul_ceil(percent.mul_ceil(*amount)),
+			};
+			let treasury_amount: AssetBalance<T> = distribute_percent.mul_ceil(amount.saturating_sub(player_amount));
+			let burned_amount = amount.saturating_sub(player_amount).saturating_sub(treasury_amount);
+
+			if !player_amount.is_zero() {
+				T::FeaturedAssets::transfer(*asset_id, server, player, player_amount)?;
+			}
+			if !treasury_amount.is_zero() {
+				T::FeaturedAssets::transfer(*asset_id, server, &treasury, treasury_amount)?;
+			}
+			// Step.3 burn the rest of minted assets
+			if !burned_amount.is_zero() {
+				T::FeaturedAssets::burn(*asset_id, server, burned_amount)?;
+			}
+
+			settlements.push(AssetSettlement {
+				asset_id: *asset_id,
+				player: player_amount,
+				treasury: treasury_amount,
+				burned: burned_amount,
+			});
+		}
+
+		// Step.4 award experience to player according to result
+		let experience = percent.mul_floor(T::BaseExperience::get());
+		if experience > 0 {
+			T::Experience::award_experience(player, experience);
+		}
+		Ok((percent, settlements))
+	}
+
+	/// settle the final result of a single player dungeon instance
+	///
+	/// Distribute assets, update leaderboard and set the instance ended. Should be called
+	/// in a storage transaction.
+	fn settle_report(
+		ticket_id: T::Hash,
+		ins: &mut DungeonInstanceOf<T>,
+		dungeon: &DungeonInfoOf<T>,
+		server: T::AccountId,
+		result: DungeonReportState,
+		report_at: T::BlockNumber,
+	) -> DispatchResult {
+		let (percent, settlements) = Self::distribute(dungeon, &dungeon.provide_assets, &server, &ins.player, &result)?;
+
+		Self::update_leaderboard(ins.id, &ins.player, ticket_id, &result, report_at);
+		Self::release_server(&server);
+
+		// set instance status
+		ins.status = DungeonInstanceStatus::Ended {
+			server: server.clone(),
+			report_at,
+			report_state: result,
+		};
+
+		Self::deposit_event(Event::DungeonEnded(ins.id, ins.player.clone(), server, ticket_id, percent));
+		Self::deposit_event(Event::DungeonSettled(ticket_id, ins.player.clone(), settlements));
+		Ok(())
+	}
+
+	/// distribute the provided assets equally shared by players according to their results
+	///
+	/// The remainder of shares is burned from the server. Should be called in a storage transaction.
+	fn settle_party(
+		dungeon: &DungeonInfoOf<T>,
+		server: &T::AccountId,
+		results: &[(T::AccountId, DungeonReportState)],
+	) -> Result<Vec<(Percent, Vec<AssetSettlementOf<T>>)>, DispatchError> {
+		if results.is_empty() {
+			return Ok(Vec::new());
+		}
+		let count: AssetBalance<T> = (results.len() as u32).into();
+		let shares: Vec<AssetAmountPair<T>> = dungeon.provide_assets.iter()
+			.map(|(asset_id, amount)| (*asset_id, *amount / count))
+			.collect();
+
+		let mut settled = Vec::with_capacity(results.len());
+		for (player, result) in results.iter() {
+			settled.push(Self::distribute(dungeon, &shares, server, player, result)?);
+		}
+		for ((asset_id, amount), (_, share)) in dungeon.provide_assets.iter().zip(shares.iter()) {
+			let remainder = amount.saturating_sub(share.saturating_mul(count));
+			if !remainder.is_zero() {
+				T::FeaturedAssets::burn(*asset_id, server, remainder)?;
+			}
+		}
+		Ok(settled)
+	}
+
+	/// settle the final results of a party dungeon instance
+	///
+	/// Distribute the shares of assets, update leaderboard and set the instance ended. Should be
+	/// called in a storage transaction.
+	fn settle_party_report(
+		ticket_id: T::Hash,
+		ins: &mut DungeonInstanceOf<T>,
+		dungeon: &DungeonInfoOf<T>,
+		server: T::AccountId,
+		results: Vec<(T::AccountId, DungeonReportState)>,
+		report_at: T::BlockNumber,
+	) -> DispatchResult {
+		let settled = Self::settle_party(dungeon, &server, &results)?;
+
+		for ((member, result), (percent, settlements)) in results.iter().zip(settled.into_iter()) {
+			Self::update_leaderboard(ins.id, member, ticket_id, result, report_at);
+
+			Self::deposit_event(Event::DungeonEnded(ins.id, member.clone(), server.clone(), ticket_id, percent));
+			Self::deposit_event(Event::DungeonSettled(ticket_id, member.clone(), settlements));
+		}
+		Self::release_server(&server);
+
+		// set instance status
+		ins.status = DungeonInstanceStatus::PartyEnded {
+			server: server.clone(),
+			report_at,
+			report_states: results,
+		};
+
+		Self::deposit_event(Event::DungeonPartyEnded(ins.id, ins.player.clone(), server, ticket_id));
+		Ok(())
+	}
+
+	/// settle a reported result after the dispute period or the resolution of dispute
+	///
+	/// A party is settled with the reported result of every member, or all with the overriding
+	/// result. Should be called in a storage transaction.
+	fn settle_reported(
+		ticket_id: T::Hash,
+		ins: &mut DungeonInstanceOf<T>,
+		dungeon: &DungeonInfoOf<T>,
+		server: T::AccountId,
+		result: DungeonReportState,
+		overridden: bool,
+		report_at: T::BlockNumber,
+	) -> DispatchResult {
+		let party_results = PartyReports::<T>::take(ticket_id);
+		if party_results.is_empty() {
+			return Self::settle_report(ticket_id, ins, dungeon, server, result, report_at);
+		}
+
+		let results = if overridden {
+			party_results.into_iter().map(|(member, _)| (member, result.clone())).collect()
+		} else {
+			party_results
+		};
+		Self::settle_party_report(ticket_id, ins, dungeon, server, results, report_at)
+	}
+
+	/// the weight of settling the provided assets of a dungeon to the members
+	///
+	/// Every asset of a member is transferred to player and treasury and burned, then the
+	/// member gains experience and enters the leaderboard.
+	fn settle_weight(members: usize, assets: usize) -> Weight {
+		let ops = (members as Weight).saturating_mul(2 + (assets as Weight).saturating_mul(6));
+		T::DbWeight::get().reads_writes(2 + ops, 2 + ops)
+	}
+
+	/// close an expired dungeon instance, returns the consumed weight
+	///
+	/// A booked ticket is refunded, a started instance is settled with `ExpiredReportState`,
+	/// and a reported result is settled when its dispute period is over.
+	fn close_expired(ticket_id: T::Hash, now: T::BlockNumber) -> Weight {
+		DungeonInstances::<T>::mutate(ticket_id, |maybe_instance| {
+			let ins = match maybe_instance.as_mut() {
+				Some(ins) => ins,
+				None => return T::DbWeight::get().reads(1),
+			};
+			let dungeon = match Dungeons::<T>::get(ins.id) {
+				Some(dungeon) => dungeon,
+				None => return T::DbWeight::get().reads(2),
+			};
+			let weight = T::DbWeight::get().reads_writes(3, 1);
+
+			match ins.status.clone() {
+				DungeonInstanceStatus::Booked{ close_due } if close_due <= now => {
+					let members = Self::members_of(&ticket_id, ins);
+					for member in members.iter() {
+						T::Currency::unreserve(member, ins.ticket_price);
+					}
+					ins.status = DungeonInstanceStatus::Closed;
+
+					Self::deposit_event(Event::DungeonTicketExpired(ins.id, ins.player.clone(), ticket_id));
+					let count = members.len() as Weight;
+					weight.saturating_add(T::DbWeight::get().reads_writes(count, count))
+				},
+				DungeonInstanceStatus::Started{ server, close_due } if close_due <= now => {
+					let result = T::ExpiredReportState::get();
+					let results: Vec<(T::AccountId, DungeonReportState)> = Self::members_of(&ticket_id, ins).into_iter()
+						.map(|member| (member, result.clone()))
+						.collect();
+					let settled = with_transaction(|| {
+						match Self::settle_party(&dungeon, &server, &results) {
+							Ok(settled) => TransactionOutcome::Commit(Ok(settled)),
+							Err(err) => TransactionOutcome::Rollback(Err(err)),
+						}
+					});
+					ins.status = DungeonInstanceStatus::Closed;
+					Self::release_server(&server);
+
+					// the assets stay on the server if distribution failed
+					let settled = settled.unwrap_or_default();
+					for (index, (member, _)) in results.iter().enumerate() {
+						let (percent, settlements) = settled.get(index).cloned().unwrap_or_default();
+						Self::deposit_event(Event::DungeonExpired(ins.id, member.clone(), server.clone(), ticket_id, percent));
+						Self::deposit_event(Event::DungeonSettled(ticket_id, member.clone(), settlements));
+					}
+					weight.saturating_add(Self::settle_weight(results.len(), dungeon.provide_assets.len()))
+				},
+				DungeonInstanceStatus::Reported{ server, report_at, report_state, dispute_due } if dispute_due <= now => {
+					let members = PartyReports::<T>::decode_len(ticket_id).unwrap_or(0).max(1);
+					let settled = with_transaction(|| {
+						match Self::settle_reported(ticket_id, ins, &dungeon, server.clone(), report_state, false, report_at) {
+							Ok(()) => TransactionOutcome::Commit(Ok(())),
+							Err(err) => TransactionOutcome::Rollback(Err(err)),
+						}
+					});
+					// the assets stay on the server if distribution failed
+					if settled.is_err() {
+						PartyReports::<T>::remove(ticket_id);
+						ins.status = DungeonInstanceStatus::Closed;
+						Self::release_server(&server);
+					}
+					weight.saturating_add(Self::settle_weight(members, dungeon.provide_assets.len()))
+				},
+				_ => weight,
+			}
+		})
+	}
 }
@@ -0,0 +1,62 @@
+//! Storage migrations of the dungeons pallet.
+
+use super::*;
+use sp_std::collections::btree_set::BTreeSet;
+
+/// The dungeon instance before `Releases::V2`.
+#[derive(Decode)]
+struct OldDungeonInstance<DungeonId, AccountId, BlockNumber> {
+	id: DungeonId,
+	player: AccountId,
+	created_at: BlockNumber,
+	status: DungeonInstanceStatus<AccountId, BlockNumber>,
+}
+
+/// Keep the ticket price in every dungeon instance.
+///
+/// The price reserved at booking is not recorded before, the current ticket price of dungeon
+/// is the best guess, zero if the dungeon is gone.
+pub fn migrate_to_instance_price<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+	DungeonInstances::<T>::translate::<OldDungeonInstance<T::DungeonId, T::AccountId, T::BlockNumber>, _>(|_, old| {
+		translated = translated.saturating_add(1);
+		let ticket_price = Dungeons::<T>::get(old.id).map_or_else(Zero::zero, |dungeon| dungeon.ticket_price);
+		Some(DungeonInstance {
+			id: old.id,
+			player: old.player,
+			created_at: old.created_at,
+			ticket_price,
+			status: old.status,
+		})
+	});
+
+	T::DbWeight::get().reads_writes(translated.saturating_mul(2), translated)
+}
+
+/// Put every open dungeon instance into `InstanceExpiries` at its due block, the instances
+/// which are already due are closed in the next block.
+pub fn migrate_to_instance_expiries<T: Config>() -> Weight {
+	let next_block = frame_system::Module::<T>::block_number() + One::one();
+	let listed: BTreeSet<T::Hash> = InstanceExpiries::<T>::iter()
+		.flat_map(|(_, list)| list)
+		.collect();
+
+	let mut read: Weight = listed.len() as Weight;
+	let mut written: Weight = 0;
+	for (ticket_id, ins) in DungeonInstances::<T>::iter() {
+		read = read.saturating_add(1);
+		let due = match ins.status {
+			DungeonInstanceStatus::Booked{ close_due } => close_due,
+			DungeonInstanceStatus::Started{ close_due, .. } => close_due,
+			DungeonInstanceStatus::Reported{ dispute_due, .. } => dispute_due,
+			_ => continue,
+		};
+		if listed.contains(&ticket_id) {
+			continue;
+		}
+		Pallet::<T>::insert_expiry(due.max(next_block), ticket_id);
+		written = written.saturating_add(1);
+	}
+
+	T::DbWeight::get().reads_writes(read.saturating_add(written), written)
+}
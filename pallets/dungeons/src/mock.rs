@@ -0,0 +1,210 @@
+use crate as mc_dungeons;
+use std::cell::RefCell;
+use frame_support::{assert_ok, parameter_types, traits::{Get, GenesisBuild}};
+use frame_system::{EnsureOneOf, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	Percent,
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup, Dispatchable},
+};
+use mc_support::{
+	primitives::DungeonReportState,
+	traits::{ManagerAccessor, RandomHash, EnsureManager, Experience, IssuerRole, FreezerRole},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		Assets: mc_featured_assets::{Module, Call, Storage, Event<T>},
+		Dungeons: mc_dungeons::{Module, Call, Storage, Config, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// The admin and server of dungeons, account 3 is also a server.
+pub const ADMIN: u64 = 1;
+/// The treasury account.
+pub const TREASURY: u64 = 99;
+
+pub struct TestAdmin;
+impl ManagerAccessor<u64> for TestAdmin {
+	fn get_owner_id() -> u64 { TREASURY }
+	fn is_admin(who: &u64) -> bool { *who == ADMIN }
+	fn is_issuer(who: &u64) -> bool { *who == ADMIN }
+	fn is_freezer(who: &u64) -> bool { *who == ADMIN }
+	fn is_server(who: &u64) -> bool { *who == ADMIN || *who == 3 }
+}
+
+pub struct TestRandomHash;
+impl RandomHash<H256> for TestRandomHash {
+	fn generate_by_subject(_: &[u8]) -> H256 { H256::zero() }
+}
+
+parameter_types! {
+	pub const AssetDepositBase: u64 = 1;
+	pub const AssetDepositPerZombie: u64 = 1;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u64 = 1;
+	pub const MetadataDepositPerByte: u64 = 1;
+}
+
+impl mc_featured_assets::Config for Test {
+	type Currency = Balances;
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type ManagerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestAdmin, u64>>;
+	type IssuerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestAdmin, u64, IssuerRole>>;
+	type FreezerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestAdmin, u64, FreezerRole>>;
+	type AssetDepositBase = AssetDepositBase;
+	type AssetDepositPerZombie = AssetDepositPerZombie;
+	type StringLimit = StringLimit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type WeightInfo = ();
+	type AssetAdmin = TestAdmin;
+	type RandomNumber = ();
+}
+
+parameter_types! {
+	pub const TicketClosingGap: u64 = 10;
+	pub const TicketPlayingGap: u64 = 10;
+	pub const AssetDistributionPercent: Percent = Percent::from_percent(90);
+	pub const ExpiredReportState: DungeonReportState = DungeonReportState::Lose;
+	pub const TicketCancellationFee: Percent = Percent::from_percent(10);
+	pub const LeaderboardSize: u32 = 2;
+	pub const MaxPartySize: u32 = 2;
+	pub const ServerBond: u64 = 10;
+	pub const DisputeBond: u64 = 5;
+	pub const BaseExperience: u32 = 100;
+	pub const MaxExpiriesPerBlock: u32 = 2;
+}
+
+thread_local! {
+	static DISPUTE_PERIOD: RefCell<u64> = RefCell::new(0);
+}
+
+/// No dispute period by default, results are settled immediately.
+pub struct DisputePeriod;
+impl Get<u64> for DisputePeriod {
+	fn get() -> u64 { DISPUTE_PERIOD.with(|v| *v.borrow()) }
+}
+
+pub fn set_dispute_period(period: u64) {
+	DISPUTE_PERIOD.with(|v| *v.borrow_mut() = period);
+}
+
+thread_local! {
+	static EXPERIENCE: RefCell<Vec<(u64, u32)>> = RefCell::new(Vec::new());
+}
+
+/// Record the experience awarded to players.
+pub struct TestExperience;
+impl Experience<u64> for TestExperience {
+	fn award_experience(who: &u64, amount: u32) -> u32 {
+		EXPERIENCE.with(|v| v.borrow_mut().push((*who, amount)));
+		0
+	}
+}
+
+pub fn awarded_experience() -> Vec<(u64, u32)> {
+	EXPERIENCE.with(|v| v.borrow().clone())
+}
+
+impl mc_dungeons::Config for Test {
+	type Event = Event;
+	type DungeonId = u32;
+	type Balance = u64;
+	type Currency = Balances;
+	type ManagerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestAdmin, u64>>;
+	type AssetAdmin = TestAdmin;
+	type RandomNumber = ();
+	type RandomHash = TestRandomHash;
+	type FeaturedAssets = Assets;
+	type TicketClosingGap = TicketClosingGap;
+	type TicketPlayingGap = TicketPlayingGap;
+	type AssetDistributionPercent = AssetDistributionPercent;
+	type ExpiredReportState = ExpiredReportState;
+	type TicketCancellationFee = TicketCancellationFee;
+	type LeaderboardSize = LeaderboardSize;
+	type MaxPartySize = MaxPartySize;
+	type ServerBond = ServerBond;
+	type DisputePeriod = DisputePeriod;
+	type DisputeBond = DisputeBond;
+	type Experience = TestExperience;
+	type BaseExperience = BaseExperience;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ADMIN, 100), (2, 100), (3, 100), (4, 100), (TREASURY, 1)],
+	}.assimilate_storage(&mut t).unwrap();
+	GenesisBuild::<Test>::assimilate_storage(&mc_dungeons::GenesisConfig::default(), &mut t).unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Create the featured asset 0 and the dungeon 1 which provides 100 of it, ticket price is 10.
+/// ADMIN is registered as the server of dungeon 1.
+pub fn setup_dungeon() {
+	assert_ok!(Call::Assets(mc_featured_assets::Call::force_create(0, ADMIN, 10, 1)).dispatch(Origin::root()));
+	assert_ok!(Dungeons::create(Origin::signed(ADMIN), 1, 10, vec![(0, 100)]));
+	assert_ok!(Dungeons::register_server(Origin::signed(ADMIN), vec![1]));
+}
@@ -0,0 +1,756 @@
+// Tests to be written here
+
+use crate::mock::*;
+use crate::*;
+use codec::Encode;
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use frame_support::{
+	assert_noop, assert_ok, error::BadOrigin, Blake2_128Concat, StorageHasher,
+	storage::migration::put_storage_value,
+	traits::{OnInitialize, OnRuntimeUpgrade},
+};
+use mc_support::traits::FeaturedAssets;
+
+fn ticket_id(id: u32, player: u64, at: u64) -> H256 {
+	BlakeTwo256::hash_of(&(id.encode(), &player, &at))
+}
+
+#[test]
+fn manager_origin_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		// root
+		assert_ok!(Dungeons::create(Origin::root(), 2, 10, vec![(0, 100)]));
+		// manager
+		assert_ok!(Dungeons::modify_price(Origin::signed(ADMIN), 2, 20));
+		// others
+		assert_noop!(Dungeons::modify_price(Origin::signed(2), 2, 30), BadOrigin);
+		assert_noop!(Dungeons::create(Origin::none(), 3, 10, vec![]), BadOrigin);
+	});
+}
+
+#[test]
+fn buy_ticket_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::Booked { close_due: 11 }
+		);
+		assert_eq!(Dungeons::instance_expiries(11), Some(vec![ticket]));
+	});
+}
+
+#[test]
+fn cancel_ticket_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::cancel_ticket(Origin::signed(2), ticket));
+
+		// 10% of ticket price is charged
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 99);
+		assert_eq!(Balances::free_balance(&TREASURY), 2);
+		assert_eq!(Dungeons::dungeon_instances(ticket).unwrap().status, DungeonInstanceStatus::Closed);
+		assert_eq!(Dungeons::instance_expiries(11), None);
+	});
+}
+
+#[test]
+fn cancel_ticket_of_others_should_not_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		assert_noop!(
+			Dungeons::cancel_ticket(Origin::signed(3), ticket_id(1, 2, 1)),
+			Error::<Test>::NotTicketOwner
+		);
+	});
+}
+
+#[test]
+fn cancel_ticket_should_refund_reserved_price() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+		assert_ok!(Dungeons::modify_price(Origin::signed(ADMIN), 1, 20));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::cancel_ticket(Origin::signed(2), ticket));
+
+		// the fee is charged from the price reserved at booking
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 99);
+		assert_eq!(Balances::free_balance(&TREASURY), 2);
+	});
+}
+
+#[test]
+fn cancel_unknown_ticket_should_not_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+
+		assert_noop!(
+			Dungeons::cancel_ticket(Origin::signed(2), ticket_id(1, 2, 1)),
+			Error::<Test>::UnknownInstance
+		);
+	});
+}
+
+#[test]
+fn cancel_started_ticket_should_not_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+		assert_noop!(
+			Dungeons::cancel_ticket(Origin::signed(2), ticket),
+			Error::<Test>::InstanceStatusShouldBeBooked
+		);
+	});
+}
+
+#[test]
+fn cancel_ticket_twice_should_not_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::cancel_ticket(Origin::signed(2), ticket));
+		assert_noop!(
+			Dungeons::cancel_ticket(Origin::signed(2), ticket),
+			Error::<Test>::InstanceStatusShouldBeBooked
+		);
+	});
+}
+
+#[test]
+fn expired_ticket_should_be_refunded() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		Dungeons::on_initialize(10);
+		assert_eq!(Balances::reserved_balance(&2), 10);
+
+		Dungeons::on_initialize(11);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 100);
+		assert_eq!(Dungeons::dungeon_instances(ticket).unwrap().status, DungeonInstanceStatus::Closed);
+		assert_eq!(Dungeons::instance_expiries(11), None);
+	});
+}
+
+#[test]
+fn expired_ticket_should_refund_reserved_price() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+		assert_ok!(Dungeons::modify_price(Origin::signed(ADMIN), 1, 20));
+
+		Dungeons::on_initialize(11);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 100);
+	});
+}
+
+#[test]
+fn expired_started_instance_should_be_settled() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+		assert_eq!(Dungeons::instance_expiries(11), Some(vec![ticket]));
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().running, 1);
+
+		// settled as `ExpiredReportState`, nothing for the player
+		Dungeons::on_initialize(11);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 0);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 90);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 0);
+		assert_eq!(Dungeons::dungeon_instances(ticket).unwrap().status, DungeonInstanceStatus::Closed);
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().running, 0);
+		assert_eq!(Dungeons::instance_expiries(11), None);
+		assert!(System::events().iter().any(|record| record.event == crate::mock::Event::mc_dungeons(crate::Event::DungeonExpired(
+			1, 2, ADMIN, ticket, Percent::from_percent(0),
+		))));
+	});
+}
+
+#[test]
+fn expired_instances_over_limit_should_be_delayed() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(3), 1));
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(4), 1));
+
+		Dungeons::on_initialize(11);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::reserved_balance(&3), 0);
+		assert_eq!(Balances::reserved_balance(&4), 10);
+		assert_eq!(Dungeons::instance_expiries(12), Some(vec![ticket_id(1, 4, 1)]));
+
+		Dungeons::on_initialize(12);
+		assert_eq!(Balances::reserved_balance(&4), 0);
+		assert_eq!(Dungeons::dungeon_instances(ticket_id(1, 4, 1)).unwrap().status, DungeonInstanceStatus::Closed);
+		assert_eq!(Dungeons::instance_expiries(12), None);
+	});
+}
+
+#[test]
+fn end_should_settle_assets() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 100);
+
+		assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket, DungeonReportState::ScoredWin(Percent::from_percent(50))));
+		// 90% of 50% to player, 90% of the rest to treasury, and the others are burned
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 45);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 50);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 0);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::total_supply(0), 95);
+		assert_eq!(Dungeons::instance_expiries(11), None);
+		// experience is scaled by the result
+		assert_eq!(awarded_experience(), vec![(2, 50)]);
+	});
+}
+
+#[test]
+fn leaderboard_should_keep_best_results() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		for player in 2..=4 {
+			assert_ok!(Dungeons::buy_ticket(Origin::signed(player), 1));
+			assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket_id(1, player, 1)));
+		}
+
+		assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket_id(1, 2, 1), DungeonReportState::ScoredWin(Percent::from_percent(50))));
+		assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket_id(1, 3, 1), DungeonReportState::PerfectWin));
+		assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket_id(1, 4, 1), DungeonReportState::ScoredWin(Percent::from_percent(30))));
+
+		let board = Dungeons::leaderboards(1);
+		assert_eq!(board.len(), 2);
+		assert_eq!(board[0].player, 3);
+		assert_eq!(board[0].report_state, DungeonReportState::PerfectWin);
+		assert_eq!(board[1].player, 2);
+		assert_eq!(board[1].report_at, 1);
+	});
+}
+
+#[test]
+fn player_runs_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+		System::set_block_number(2);
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let runs = Dungeons::player_runs(&2);
+		assert_eq!(runs.len(), 2);
+		assert_eq!(runs[0].0, ticket_id(1, 2, 1));
+		assert_eq!(runs[1].0, ticket_id(1, 2, 2));
+		assert!(Dungeons::player_runs(&3).is_empty());
+	});
+}
+
+/// The assets received by the player of dungeon 1 with the report ranks and result.
+fn settled_with_ranks(report_ranks: Vec<(DungeonReportState, Percent)>, result: DungeonReportState) -> u64 {
+	setup_dungeon();
+	assert_ok!(Dungeons::modify_distribution_ratio(Origin::signed(ADMIN), 1, report_ranks));
+	assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+	let ticket = ticket_id(1, 2, 1);
+	assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+	assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket, result));
+	<Assets as FeaturedAssets<u64>>::balance(0, 2)
+}
+
+fn report_ranks() -> Vec<(DungeonReportState, Percent)> {
+	vec![
+		(DungeonReportState::Lose, Percent::from_percent(10)),
+		(DungeonReportState::ScoredWin(Percent::from_percent(50)), Percent::from_percent(40)),
+		(DungeonReportState::PerfectWin, Percent::from_percent(80)),
+	]
+}
+
+#[test]
+fn report_ranks_should_be_preferred() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(settled_with_ranks(report_ranks(), DungeonReportState::PerfectWin), 80);
+		// 90% of the rest goes to treasury, the others are burned
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 18);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 0);
+	});
+}
+
+#[test]
+fn zero_score_should_get_lose_rank() {
+	new_test_ext().execute_with(|| {
+		let result = DungeonReportState::ScoredWin(Percent::from_percent(0));
+		assert_eq!(settled_with_ranks(report_ranks(), result), 10);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 81);
+	});
+}
+
+#[test]
+fn score_on_rank_edge_should_get_the_rank() {
+	new_test_ext().execute_with(|| {
+		let result = DungeonReportState::ScoredWin(Percent::from_percent(50));
+		assert_eq!(settled_with_ranks(report_ranks(), result), 40);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 54);
+	});
+}
+
+#[test]
+fn score_between_ranks_should_be_interpolated() {
+	new_test_ext().execute_with(|| {
+		let result = DungeonReportState::ScoredWin(Percent::from_percent(75));
+		assert_eq!(settled_with_ranks(report_ranks(), result), 60);
+	});
+}
+
+#[test]
+fn no_report_ranks_should_fallback_to_distribution_percent() {
+	new_test_ext().execute_with(|| {
+		// 90% of the scored 50%
+		let result = DungeonReportState::ScoredWin(Percent::from_percent(50));
+		assert_eq!(settled_with_ranks(vec![], result), 45);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 50);
+	});
+}
+
+fn party_ticket_id(id: u32, leader: u64, at: u64) -> H256 {
+	BlakeTwo256::hash_of(&(id.encode(), &leader, &at, b"party"))
+}
+
+#[test]
+fn book_and_join_party_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::book_party(Origin::signed(2), 1));
+
+		let ticket = party_ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::join_party(Origin::signed(3), ticket));
+		assert_eq!(Dungeons::parties(ticket), vec![2, 3]);
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		assert_eq!(Balances::reserved_balance(&3), 10);
+		assert_eq!(Dungeons::player_runs(&3)[0].0, ticket);
+
+		assert_noop!(Dungeons::join_party(Origin::signed(3), ticket), Error::<Test>::AlreadyInParty);
+		assert_noop!(Dungeons::join_party(Origin::signed(4), ticket), Error::<Test>::PartyIsFull);
+
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(4), 1));
+		assert_noop!(
+			Dungeons::join_party(Origin::signed(3), ticket_id(1, 4, 1)),
+			Error::<Test>::NotPartyInstance
+		);
+	});
+}
+
+#[test]
+fn end_party_should_split_rewards() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::book_party(Origin::signed(2), 1));
+
+		let ticket = party_ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::join_party(Origin::signed(3), ticket));
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Balances::free_balance(&3), 90);
+		assert_eq!(Balances::free_balance(&ADMIN), 110);
+
+		assert_noop!(
+			Dungeons::end(Origin::signed(ADMIN), ticket, DungeonReportState::PerfectWin),
+			Error::<Test>::IsPartyInstance
+		);
+		assert_noop!(
+			Dungeons::end_party(Origin::signed(ADMIN), ticket, vec![(2, DungeonReportState::PerfectWin)]),
+			Error::<Test>::PartyReportsMismatch
+		);
+
+		let results = vec![(2, DungeonReportState::PerfectWin), (3, DungeonReportState::Lose)];
+		assert_ok!(Dungeons::end_party(Origin::signed(ADMIN), ticket, results.clone()));
+		// each member shares 50 of provided assets
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 45);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 3), 0);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, TREASURY), 50);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 0);
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::PartyEnded { server: ADMIN, report_at: 1, report_states: results }
+		);
+		assert_eq!(Dungeons::leaderboards(1).len(), 1);
+		assert_eq!(Dungeons::instance_expiries(11), None);
+	});
+}
+
+#[test]
+fn start_party_should_be_atomic() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::book_party(Origin::signed(2), 1));
+
+		let ticket = party_ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::join_party(Origin::signed(3), ticket));
+		// the second member only keeps the reserved ticket price, transfer would kill the account
+		Balances::make_free_balance_be(&3, 0);
+		let status = Dungeons::dungeon_instances(ticket).unwrap().status;
+		let server_balance = Balances::free_balance(&ADMIN);
+
+		assert!(Dungeons::start(Origin::signed(ADMIN), ticket).is_err());
+		// the first member is not charged
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Balances::reserved_balance(&3), 10);
+		assert_eq!(Balances::free_balance(&ADMIN), server_balance);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 0);
+		assert_eq!(Dungeons::dungeon_instances(ticket).unwrap().status, status);
+	});
+}
+
+#[test]
+fn start_party_should_charge_reserved_price() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::book_party(Origin::signed(2), 1));
+		assert_ok!(Dungeons::modify_price(Origin::signed(ADMIN), 1, 20));
+
+		let ticket = party_ticket_id(1, 2, 1);
+		// joins at the price of the party
+		assert_ok!(Dungeons::join_party(Origin::signed(3), ticket));
+		assert_eq!(Balances::reserved_balance(&3), 10);
+
+		let server_balance = Balances::free_balance(&ADMIN);
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Balances::reserved_balance(&3), 0);
+		assert_eq!(Balances::free_balance(&3), 90);
+		assert_eq!(Balances::free_balance(&ADMIN), server_balance + 20);
+	});
+}
+
+#[test]
+fn cancel_party_should_refund_members() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::book_party(Origin::signed(2), 1));
+
+		let ticket = party_ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::join_party(Origin::signed(3), ticket));
+		assert_noop!(Dungeons::cancel_ticket(Origin::signed(3), ticket), Error::<Test>::NotTicketOwner);
+		assert_ok!(Dungeons::cancel_ticket(Origin::signed(2), ticket));
+
+		// only the leader is charged
+		assert_eq!(Balances::free_balance(&2), 99);
+		assert_eq!(Balances::reserved_balance(&3), 0);
+		assert_eq!(Balances::free_balance(&3), 100);
+		assert_eq!(Balances::free_balance(&TREASURY), 2);
+	});
+}
+
+#[test]
+fn register_server_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_eq!(Balances::reserved_balance(&ADMIN), 10);
+		assert_noop!(Dungeons::register_server(Origin::signed(ADMIN), vec![1]), Error::<Test>::ServerExists);
+		assert_noop!(Dungeons::register_server(Origin::signed(3), vec![2]), Error::<Test>::UnknownDungeon);
+		assert_noop!(Dungeons::register_server(Origin::signed(4), vec![1]), Error::<Test>::NoPermission);
+
+		assert_ok!(Dungeons::register_server(Origin::signed(3), vec![]));
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+		let ticket = ticket_id(1, 2, 1);
+		assert_noop!(Dungeons::start(Origin::signed(3), ticket), Error::<Test>::NotHostingServer);
+		assert_noop!(Dungeons::start(Origin::signed(4), ticket), Error::<Test>::NotHostingServer);
+
+		assert_ok!(Dungeons::modify_server_dungeons(Origin::signed(3), vec![1]));
+		assert_ok!(Dungeons::start(Origin::signed(3), ticket));
+		assert_eq!(Dungeons::servers(3).unwrap().running, 1);
+	});
+}
+
+#[test]
+fn unregister_running_server_should_not_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		let ticket = ticket_id(1, 2, 1);
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+		assert_noop!(Dungeons::unregister_server(Origin::signed(ADMIN)), Error::<Test>::ServerIsRunning);
+
+		assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket, DungeonReportState::Lose));
+		assert_ok!(Dungeons::unregister_server(Origin::signed(ADMIN)));
+		assert_eq!(Balances::reserved_balance(&ADMIN), 0);
+		assert_eq!(Dungeons::servers(ADMIN), None);
+	});
+}
+
+#[test]
+fn slash_server_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_noop!(Dungeons::slash_server(Origin::signed(2), ADMIN, 4), BadOrigin);
+		assert_noop!(Dungeons::slash_server(Origin::root(), 2, 4), Error::<Test>::UnknownServer);
+
+		assert_ok!(Dungeons::slash_server(Origin::root(), ADMIN, 4));
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().bond, 6);
+		assert_eq!(Balances::reserved_balance(&ADMIN), 6);
+		assert_eq!(Balances::free_balance(&TREASURY), 5);
+
+		// no more than the bond
+		assert_ok!(Dungeons::slash_server(Origin::root(), ADMIN, 100));
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().bond, 0);
+		assert_eq!(Balances::free_balance(&TREASURY), 11);
+	});
+}
+
+/// ticket of player 2 started by ADMIN and reported with the result, dispute period is 5
+fn reported_ticket(result: DungeonReportState) -> H256 {
+	set_dispute_period(5);
+	setup_dungeon();
+	assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+	let ticket = ticket_id(1, 2, 1);
+	assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+	assert_ok!(Dungeons::end(Origin::signed(ADMIN), ticket, result));
+	ticket
+}
+
+#[test]
+fn reported_result_should_be_settled_after_dispute_period() {
+	new_test_ext().execute_with(|| {
+		let ticket = reported_ticket(DungeonReportState::PerfectWin);
+		assert_eq!(Dungeons::instance_expiries(6), Some(vec![ticket]));
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 0);
+
+		Dungeons::on_initialize(6);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 90);
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::Ended { server: ADMIN, report_at: 1, report_state: DungeonReportState::PerfectWin }
+		);
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().running, 0);
+	});
+}
+
+#[test]
+fn dispute_should_work() {
+	new_test_ext().execute_with(|| {
+		let ticket = reported_ticket(DungeonReportState::Lose);
+		assert_noop!(Dungeons::dispute(Origin::signed(3), ticket), Error::<Test>::NotTicketOwner);
+		assert_ok!(Dungeons::dispute(Origin::signed(2), ticket));
+		assert_eq!(Balances::reserved_balance(&2), 5);
+		assert_eq!(Dungeons::instance_expiries(6), None);
+		assert_noop!(Dungeons::dispute(Origin::signed(2), ticket), Error::<Test>::InstanceStatusShouldBeReported);
+
+		// stays disputed until resolved
+		Dungeons::on_initialize(6);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 100);
+	});
+}
+
+#[test]
+fn dispute_after_period_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let ticket = reported_ticket(DungeonReportState::Lose);
+		System::set_block_number(6);
+		assert_noop!(Dungeons::dispute(Origin::signed(2), ticket), Error::<Test>::DisputePeriodIsOver);
+	});
+}
+
+#[test]
+fn upheld_dispute_should_slash_player() {
+	new_test_ext().execute_with(|| {
+		let ticket = reported_ticket(DungeonReportState::Lose);
+		assert_ok!(Dungeons::dispute(Origin::signed(2), ticket));
+		assert_noop!(Dungeons::resolve_dispute(Origin::signed(2), ticket, None), BadOrigin);
+
+		assert_ok!(Dungeons::resolve_dispute(Origin::root(), ticket, Some(DungeonReportState::Lose)));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 85);
+		assert_eq!(Balances::free_balance(&TREASURY), 6);
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().bond, 10);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 0);
+	});
+}
+
+#[test]
+fn overridden_dispute_should_slash_server() {
+	new_test_ext().execute_with(|| {
+		let ticket = reported_ticket(DungeonReportState::Lose);
+		assert_ok!(Dungeons::dispute(Origin::signed(2), ticket));
+
+		assert_ok!(Dungeons::resolve_dispute(Origin::root(), ticket, Some(DungeonReportState::PerfectWin)));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().bond, 5);
+		assert_eq!(Balances::free_balance(&TREASURY), 6);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 90);
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::Ended { server: ADMIN, report_at: 1, report_state: DungeonReportState::PerfectWin }
+		);
+	});
+}
+
+/// party of player 2 and 3 started by ADMIN and reported with the results, dispute period is 5
+fn reported_party(results: Vec<(u64, DungeonReportState)>) -> H256 {
+	set_dispute_period(5);
+	setup_dungeon();
+	assert_ok!(Dungeons::book_party(Origin::signed(2), 1));
+
+	let ticket = party_ticket_id(1, 2, 1);
+	assert_ok!(Dungeons::join_party(Origin::signed(3), ticket));
+	assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket));
+	assert_ok!(Dungeons::end_party(Origin::signed(ADMIN), ticket, results));
+	ticket
+}
+
+#[test]
+fn reported_party_should_be_settled_after_dispute_period() {
+	new_test_ext().execute_with(|| {
+		let results = vec![(2, DungeonReportState::PerfectWin), (3, DungeonReportState::Lose)];
+		let ticket = reported_party(results.clone());
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::Reported {
+				server: ADMIN,
+				report_at: 1,
+				report_state: DungeonReportState::PerfectWin,
+				dispute_due: 6,
+			}
+		);
+		assert_eq!(Dungeons::party_reports(ticket), results);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 0);
+
+		Dungeons::on_initialize(6);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 45);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 3), 0);
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::PartyEnded { server: ADMIN, report_at: 1, report_states: results }
+		);
+		assert!(Dungeons::party_reports(ticket).is_empty());
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().running, 0);
+	});
+}
+
+#[test]
+fn party_dispute_should_work() {
+	new_test_ext().execute_with(|| {
+		let ticket = reported_party(vec![(2, DungeonReportState::Lose), (3, DungeonReportState::Lose)]);
+		// the leader disputes for the party
+		assert_noop!(Dungeons::dispute(Origin::signed(3), ticket), Error::<Test>::NotTicketOwner);
+		assert_ok!(Dungeons::dispute(Origin::signed(2), ticket));
+		assert_eq!(Balances::reserved_balance(&2), 5);
+
+		// stays disputed until resolved
+		Dungeons::on_initialize(6);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, ADMIN), 100);
+
+		// overriding result applies to every member
+		assert_ok!(Dungeons::resolve_dispute(Origin::root(), ticket, Some(DungeonReportState::PerfectWin)));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(Dungeons::servers(ADMIN).unwrap().bond, 5);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 2), 45);
+		assert_eq!(<Assets as FeaturedAssets<u64>>::balance(0, 3), 45);
+		assert_eq!(
+			Dungeons::dungeon_instances(ticket).unwrap().status,
+			DungeonInstanceStatus::PartyEnded {
+				server: ADMIN,
+				report_at: 1,
+				report_states: vec![(2, DungeonReportState::PerfectWin), (3, DungeonReportState::PerfectWin)],
+			}
+		);
+		assert!(Dungeons::party_reports(ticket).is_empty());
+	});
+}
+
+#[test]
+fn runtime_upgrade_should_do_nothing_on_new_chain() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Dungeons::storage_version(), Releases::V3);
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+
+		Dungeons::on_runtime_upgrade();
+		assert_eq!(Dungeons::storage_version(), Releases::V3);
+		assert_eq!(Dungeons::dungeon_instances(ticket_id(1, 2, 1)).unwrap().ticket_price, 10);
+		assert_eq!(Dungeons::instance_expiries(11), Some(vec![ticket_id(1, 2, 1)]));
+	});
+}
+
+#[test]
+fn migrate_to_instance_price_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		StorageVersion::<Test>::put(Releases::V1);
+		// id, player, created_at, status
+		let ticket = ticket_id(1, 2, 1);
+		let status = DungeonInstanceStatus::<u64, u64>::Booked { close_due: 11 };
+		put_storage_value(b"Dungeons", b"DungeonInstances", &Blake2_128Concat::hash(&ticket.encode()), (1u32, 2u64, 1u64, status.clone()));
+
+		Dungeons::on_runtime_upgrade();
+		assert_eq!(Dungeons::storage_version(), Releases::V3);
+		let ins = Dungeons::dungeon_instances(ticket).unwrap();
+		assert_eq!(ins.player, 2);
+		assert_eq!(ins.ticket_price, 10);
+		assert_eq!(ins.status, status);
+	});
+}
+
+#[test]
+fn migrate_to_instance_expiries_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_dungeon();
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(2), 1));
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(3), 1));
+		assert_ok!(Dungeons::start(Origin::signed(ADMIN), ticket_id(1, 3, 1)));
+		System::set_block_number(5);
+		assert_ok!(Dungeons::buy_ticket(Origin::signed(4), 1));
+		// the instances booked before the expiry index
+		InstanceExpiries::<Test>::remove(11);
+		System::set_block_number(12);
+		StorageVersion::<Test>::put(Releases::V2);
+
+		Dungeons::on_runtime_upgrade();
+		assert_eq!(Dungeons::storage_version(), Releases::V3);
+		// the overdue instances are closed in the next block
+		let mut overdue = Dungeons::instance_expiries(13).unwrap();
+		overdue.sort();
+		let mut expected = vec![ticket_id(1, 2, 1), ticket_id(1, 3, 1)];
+		expected.sort();
+		assert_eq!(overdue, expected);
+		// not listed twice
+		assert_eq!(Dungeons::instance_expiries(15), Some(vec![ticket_id(1, 4, 5)]));
+
+		Dungeons::on_initialize(13);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Dungeons::dungeon_instances(ticket_id(1, 3, 1)).unwrap().status, DungeonInstanceStatus::Closed);
+	});
+}
@@ -164,6 +164,15 @@ pub mod pallet {
 		/// The origin which may forcibly create or destroy an asset.
 		type ForceOrigin: EnsureOrigin<Self::Origin>;
 
+		/// The origin which may force transfers.
+		type ManagerOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The origin which may mint and burn assets.
+		type IssuerOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The origin which may freeze and thaw assets.
+		type FreezerOrigin: EnsureOrigin<Self::Origin>;
+
 		/// The basic amount of funds that must be reserved when creating a new asset class.
 		type AssetDepositBase: Get<BalanceOf<Self>>;
 
@@ -379,7 +388,7 @@ pub mod pallet {
 
 		/// Mint assets of a particular class.
 		///
-		/// The origin must be Signed and the sender must be the Issuer of the asset `id`.
+		/// The origin must be `IssuerOrigin`.
 		///
 		/// - `id`: The identifier of the asset to have some amount minted.
 		/// - `beneficiary`: The account to be credited with the minted assets.
@@ -396,9 +405,7 @@ pub mod pallet {
 			beneficiary: <T::Lookup as StaticLookup>::Source,
 			amount: T::Balance
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
-
-			ensure!(T::AssetAdmin::is_issuer(&origin), Error::<T>::NoPermission);
+			T::IssuerOrigin::ensure_origin(origin)?;
 
 			let beneficiary = T::Lookup::lookup(beneficiary)?;
 
@@ -409,7 +416,7 @@ pub mod pallet {
 
 		/// Reduce the balance of `who` by as much as possible up to `amount` assets of `id`.
 		///
-		/// Origin must be Signed and the sender should be the Manager of the asset `id`.
+		/// Origin must be `IssuerOrigin`.
 		///
 		/// Bails with `BalanceZero` if the `who` is already dead.
 		///
@@ -429,9 +436,7 @@ pub mod pallet {
 			who: <T::Lookup as StaticLookup>::Source,
 			amount: T::Balance
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
-
-			ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
+			T::IssuerOrigin::ensure_origin(origin)?;
 
 			let who = T::Lookup::lookup(who)?;
 
@@ -476,7 +481,7 @@ pub mod pallet {
 
 		/// Move some assets from one account to another.
 		///
-		/// Origin must be Signed and the sender should be the Admin of the asset `id`.
+		/// Origin must be `ManagerOrigin`.
 		///
 		/// - `id`: The identifier of the asset to have some amount transferred.
 		/// - `source`: The account to be debited.
@@ -501,7 +506,7 @@ pub mod pallet {
 			dest: <T::Lookup as StaticLookup>::Source,
 			amount: T::Balance,
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			T::ManagerOrigin::ensure_origin(origin)?;
 
 			let source = T::Lookup::lookup(source)?;
 			let mut source_account = Account::<T>::get(id, &source);
@@ -515,7 +520,6 @@ pub mod pallet {
 
 			Asset::<T>::try_mutate(id, |maybe_details| {
 				let details = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
-				ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
 
 				source_account.balance -= amount;
 				if source_account.balance < details.min_balance {
@@ -551,7 +555,7 @@ pub mod pallet {
 
 		/// Disallow further unprivileged transfers from an account.
 		///
-		/// Origin must be Signed and the sender should be the Freezer of the asset `id`.
+		/// Origin must be `FreezerOrigin`.
 		///
 		/// - `id`: The identifier of the asset to be frozen.
 		/// - `who`: The account to be frozen.
@@ -565,9 +569,8 @@ pub mod pallet {
 			#[pallet::compact] id: T::AssetId,
 			who: <T::Lookup as StaticLookup>::Source
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			T::FreezerOrigin::ensure_origin(origin)?;
 
-			ensure!(T::AssetAdmin::is_freezer(&origin), Error::<T>::NoPermission);
 			let who = T::Lookup::lookup(who)?;
 			ensure!(Account::<T>::contains_key(id, &who), Error::<T>::BalanceZero);
 
@@ -579,7 +582,7 @@ pub mod pallet {
 
 		/// Allow unprivileged transfers from an account again.
 		///
-		/// Origin must be Signed and the sender should be the Admin of the asset `id`.
+		/// Origin must be `FreezerOrigin`.
 		///
 		/// - `id`: The identifier of the asset to be frozen.
 		/// - `who`: The account to be unfrozen.
@@ -594,9 +597,8 @@ pub mod pallet {
 			id: T::AssetId,
 			who: <T::Lookup as StaticLookup>::Source
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			T::FreezerOrigin::ensure_origin(origin)?;
 
-			ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
 			let who = T::Lookup::lookup(who)?;
 			ensure!(Account::<T>::contains_key(id, &who), Error::<T>::BalanceZero);
 
@@ -608,7 +610,7 @@ pub mod pallet {
 
 		/// Disallow further unprivileged transfers for the asset class.
 		///
-		/// Origin must be Signed and the sender should be the Freezer of the asset `id`.
+		/// Origin must be `FreezerOrigin`.
 		///
 		/// - `id`: The identifier of the asset to be frozen.
 		///
@@ -620,11 +622,10 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			#[pallet::compact] id: T::AssetId
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			T::FreezerOrigin::ensure_origin(origin)?;
 
 			Asset::<T>::try_mutate(id, |maybe_details| {
 				let d = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
-				ensure!(T::AssetAdmin::is_freezer(&origin), Error::<T>::NoPermission);
 
 				d.is_frozen = true;
 
@@ -635,7 +636,7 @@ pub mod pallet {
 
 		/// Allow unprivileged transfers for the asset again.
 		///
-		/// Origin must be Signed and the sender should be the Admin of the asset `id`.
+		/// Origin must be `FreezerOrigin`.
 		///
 		/// - `id`: The identifier of the asset to be frozen.
 		///
@@ -647,11 +648,10 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			#[pallet::compact] id: T::AssetId
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			T::FreezerOrigin::ensure_origin(origin)?;
 
 			Asset::<T>::try_mutate(id, |maybe_details| {
 				let d = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
-				ensure!(T::AssetAdmin::is_admin(&origin), Error::<T>::NoPermission);
 
 				d.is_frozen = false;
 
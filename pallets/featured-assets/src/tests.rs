@@ -3,10 +3,12 @@
 use super::*;
 use crate as mc_featured_assets;
 
-use frame_support::{assert_ok, assert_noop, parameter_types};
+use frame_support::{assert_ok, assert_noop, parameter_types, error::BadOrigin};
+use frame_system::{EnsureOneOf, EnsureRoot};
 use sp_core::H256;
 use sp_runtime::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
 use pallet_balances::Error as BalancesError;
+use mc_support::traits::{EnsureManager, FreezerRole, IssuerRole};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -73,12 +75,24 @@ parameter_types! {
 	pub const MetadataDepositPerByte: u64 = 1;
 }
 
+/// Account 1 to 4 are the managers of all roles, 6 is an issuer, 7 is a freezer and 8 is an admin.
+pub struct TestManagers;
+impl ManagerAccessor<u64> for TestManagers {
+	fn get_owner_id() -> u64 { 0 }
+	fn is_admin(who: &u64) -> bool { (1..=4).contains(who) || *who == 8 }
+	fn is_issuer(who: &u64) -> bool { (1..=4).contains(who) || *who == 6 }
+	fn is_freezer(who: &u64) -> bool { (1..=4).contains(who) || *who == 7 }
+}
+
 impl Config for Test {
 	type Currency = Balances;
 	type Event = Event;
 	type Balance = u64;
 	type AssetId = u32;
 	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type ManagerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestManagers, u64>>;
+	type IssuerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestManagers, u64, IssuerRole>>;
+	type FreezerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, EnsureManager<TestManagers, u64, FreezerRole>>;
 	type AssetDepositBase = AssetDepositBase;
 	type AssetDepositPerZombie = AssetDepositPerZombie;
 	type StringLimit = StringLimit;
@@ -327,16 +341,82 @@ fn origin_guards_should_work() {
 		assert_ok!(Assets::mint(Origin::signed(1), 0, 1, 100));
 		assert_noop!(Assets::transfer_ownership(Origin::signed(2), 0, 2), Error::<Test>::NoPermission);
 		// assert_noop!(Assets::set_team(Origin::signed(2), 0, 2, 2, 2), Error::<Test>::NoPermission);
-		assert_noop!(Assets::freeze(Origin::signed(2), 0, 1), Error::<Test>::NoPermission);
-		assert_noop!(Assets::thaw(Origin::signed(2), 0, 2), Error::<Test>::NoPermission);
-		assert_noop!(Assets::mint(Origin::signed(2), 0, 2, 100), Error::<Test>::NoPermission);
-		assert_noop!(Assets::burn(Origin::signed(2), 0, 1, 100), Error::<Test>::NoPermission);
-		assert_noop!(Assets::force_transfer(Origin::signed(2), 0, 1, 2, 100), Error::<Test>::NoPermission);
+		assert_noop!(Assets::freeze(Origin::signed(5), 0, 1), BadOrigin);
+		assert_noop!(Assets::thaw(Origin::signed(5), 0, 2), BadOrigin);
+		assert_noop!(Assets::mint(Origin::signed(5), 0, 2, 100), BadOrigin);
+		assert_noop!(Assets::burn(Origin::signed(5), 0, 1, 100), BadOrigin);
+		assert_noop!(Assets::force_transfer(Origin::signed(5), 0, 1, 2, 100), BadOrigin);
+		assert_noop!(Assets::freeze_asset(Origin::signed(5), 0), BadOrigin);
+		assert_noop!(Assets::thaw_asset(Origin::signed(5), 0), BadOrigin);
 		assert_noop!(Assets::set_max_zombies(Origin::signed(2), 0, 11), Error::<Test>::NoPermission);
 		assert_noop!(Assets::destroy(Origin::signed(2), 0, 100), Error::<Test>::NoPermission);
 	});
 }
 
+#[test]
+fn manager_origin_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 0, 1, 10, 1));
+		// root
+		assert_ok!(Assets::mint(Origin::root(), 0, 1, 100));
+		// manager
+		assert_ok!(Assets::mint(Origin::signed(2), 0, 1, 100));
+		assert_eq!(Assets::balance(0, 1), 200);
+		// others
+		assert_noop!(Assets::mint(Origin::signed(5), 0, 1, 100), BadOrigin);
+		assert_noop!(Assets::mint(Origin::none(), 0, 1, 100), BadOrigin);
+	});
+}
+
+#[test]
+fn issuer_origin_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 0, 1, 10, 1));
+		assert_ok!(Assets::mint(Origin::signed(6), 0, 1, 100));
+		assert_ok!(Assets::burn(Origin::signed(6), 0, 1, 50));
+		assert_eq!(Assets::balance(0, 1), 50);
+		// freezer and admin can not issue
+		assert_noop!(Assets::mint(Origin::signed(7), 0, 1, 100), BadOrigin);
+		assert_noop!(Assets::mint(Origin::signed(8), 0, 1, 100), BadOrigin);
+		assert_noop!(Assets::burn(Origin::signed(7), 0, 1, 50), BadOrigin);
+		assert_noop!(Assets::burn(Origin::signed(8), 0, 1, 50), BadOrigin);
+	});
+}
+
+#[test]
+fn freezer_origin_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 0, 1, 10, 1));
+		assert_ok!(Assets::mint(Origin::signed(6), 0, 1, 100));
+		assert_ok!(Assets::freeze(Origin::signed(7), 0, 1));
+		assert_ok!(Assets::thaw(Origin::signed(7), 0, 1));
+		assert_ok!(Assets::freeze_asset(Origin::signed(7), 0));
+		assert_ok!(Assets::thaw_asset(Origin::signed(7), 0));
+		// issuer and admin can not freeze
+		assert_noop!(Assets::freeze(Origin::signed(6), 0, 1), BadOrigin);
+		assert_noop!(Assets::freeze(Origin::signed(8), 0, 1), BadOrigin);
+		assert_noop!(Assets::thaw(Origin::signed(6), 0, 1), BadOrigin);
+		assert_noop!(Assets::thaw(Origin::signed(8), 0, 1), BadOrigin);
+		assert_noop!(Assets::freeze_asset(Origin::signed(6), 0), BadOrigin);
+		assert_noop!(Assets::freeze_asset(Origin::signed(8), 0), BadOrigin);
+		assert_noop!(Assets::thaw_asset(Origin::signed(6), 0), BadOrigin);
+		assert_noop!(Assets::thaw_asset(Origin::signed(8), 0), BadOrigin);
+	});
+}
+
+#[test]
+fn admin_origin_should_force_transfer() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 0, 1, 10, 1));
+		assert_ok!(Assets::mint(Origin::signed(6), 0, 1, 100));
+		assert_ok!(Assets::force_transfer(Origin::signed(8), 0, 1, 2, 50));
+		assert_eq!(Assets::balance(0, 2), 50);
+		// issuer and freezer can not force transfers
+		assert_noop!(Assets::force_transfer(Origin::signed(6), 0, 1, 2, 50), BadOrigin);
+		assert_noop!(Assets::force_transfer(Origin::signed(7), 0, 1, 2, 50), BadOrigin);
+	});
+}
+
 #[test]
 fn transfer_owner_should_work() {
 	new_test_ext().execute_with(|| {
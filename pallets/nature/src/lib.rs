@@ -1,29 +1,53 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use sp_std::{fmt::Debug, prelude::*};
+use sp_std::{fmt::Debug, marker::PhantomData, prelude::*};
 use sp_runtime::{
-	RuntimeDebug, ModuleId,
+	RuntimeDebug, ModuleId, DispatchError,
 	traits::{
-		AccountIdConversion, AtLeast32BitUnsigned, StaticLookup,
-		Zero,
-		// Saturating, CheckedSub, CheckedAdd,
+		AccountIdConversion, AtLeast32BitUnsigned, StaticLookup, Hash,
+		Zero, Saturating,
+		// CheckedSub, CheckedAdd,
 	},
 };
 use frame_support::{
 	// ensure,
 	traits::{
-		Get, Randomness, Currency, ReservableCurrency
+		Get, Randomness, Currency, ReservableCurrency, BalanceStatus, ExistenceRequirement,
 	},
+	weights::Weight,
+	storage::{with_transaction, TransactionOutcome},
 	// dispatch::DispatchError,
 };
 use codec::{Encode, Decode};
 use mc_support::traits::{
-	ManagerAccessor, RandomNumber, RandomHash
+	ManagerAccessor, RandomNumber, RandomHash, AdminRole, FeaturedAssets,
 };
 
+pub mod migrations;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 pub use pallet::*;
 
 type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type AssetIdOf<T> = <<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::AssetId;
+type AssetBalanceOf<T> = <<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::Balance;
+type SpendAssetOf<T> = SpendAsset<BalanceOf<T>, AssetIdOf<T>, AssetBalanceOf<T>>;
+type SpendProposalOf<T> = SpendProposal<<T as frame_system::Config>::AccountId, SpendAssetOf<T>>;
+
+/// An index of a spend proposal.
+pub type ProposalIndex = u32;
+
+/// Ensure the origin is signed by a manager of `Role` in `Managers` of Nature.
+pub type EnsureManager<T, Role = AdminRole> = mc_support::traits::EnsureManager<
+	Pallet<T>,
+	<T as frame_system::Config>::AccountId,
+	Role,
+>;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -58,9 +82,28 @@ pub mod pallet {
 		/// The manager origin.
 		type ManagerOrigin: EnsureOrigin<Self::Origin>;
 
+		/// The minimum deposit reserved by a manager.
+		type ManagerDeposit: Get<BalanceOf<Self>>;
+
+		/// The featured assets which can be spent from the Nature account.
+		type FeaturedAssets: FeaturedAssets<Self::AccountId>;
+
+		#[pallet::constant]
+		/// Period between successive payouts of approved spends.
+		type SpendPeriod: Get<Self::BlockNumber>;
+
 		/// Something that provides randomness in the runtime.
 		type Randomness: Randomness<Self::Hash>;
 
+		#[pallet::constant]
+		/// Blocks in which a random commitment should be revealed, and in which a revealed seed
+		/// is used by `CommitReveal`.
+		type RevealPeriod: Get<Self::BlockNumber>;
+
+		#[pallet::constant]
+		/// The deposit slashed from a server whose commitment expired without reveal.
+		type RevealMissedSlash: Get<BalanceOf<Self>>;
+
 		/// Number of time we should try to generate a random number that has no modulo bias.
 		/// The larger this number, the more potential computation is used for picking the winner,
 		/// but also the more likely that the chosen winner is done fairly.
@@ -69,42 +112,217 @@ pub mod pallet {
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		// TODO on finalized
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() == Releases::V1 {
+				weight = weight.saturating_add(migrations::migrate_to_roles::<T>());
+				StorageVersion::<T>::put(Releases::V2);
+			}
+			weight
+		}
+
+		/// Reset the random nonce, and pay out the approved spends at the beginning of every
+		/// spend period.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			RandomNonce::<T>::kill();
+
+			let period = T::SpendPeriod::get();
+			let spent = if !period.is_zero() && (now % period).is_zero() {
+				Self::spend_funds()
+			} else {
+				0
+			};
+			spent.saturating_add(T::DbWeight::get().writes(1))
+		}
 	}
 
 	#[pallet::call]
 	impl<T:Config> Pallet<T> {
-		/// Setup up manager
+		/// Setup up manager, `ManagerDeposit` is reserved from the manager
 		///
 		/// - `manager`: The manager of nature
+		/// - `roles`: The roles granted to the manager
 		///
-		/// Emits `ManagerAdded` event when successful.
+		/// Emits `ManagerAdded` and `Deposit` event when successful.
 		///
 		/// Weight: `O(0)`
 		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
 		pub(super) fn set_manager(
 			origin: OriginFor<T>,
 			manager: <T::Lookup as StaticLookup>::Source,
+			roles: ManagerRoles,
 		) -> DispatchResultWithPostInfo {
 			T::ManagerOrigin::ensure_origin(origin)?;
 			let manager = T::Lookup::lookup(manager)?;
 
 			ensure!(!Managers::<T>::contains_key(manager.clone()), Error::<T>::InUse);
 
+			// Reserve deposit.
+			let deposit = T::ManagerDeposit::get();
+			T::Currency::reserve(&manager, deposit)?;
+
 			// Update manager.
 			Managers::<T>::insert(manager.clone(), ManagerInfo {
-				deposit: Zero::zero(),
-				is_admin: true,
-				is_issuer: true,
-				is_freezer: true,
+				deposit,
+				roles: roles.clone(),
 			});
 
 			// Emit event.
-			Self::deposit_event(Event::ManagerAdded(manager));
+			Self::deposit_event(Event::ManagerAdded(manager.clone(), roles));
+			Self::deposit_event(Event::Deposit(manager, deposit));
 			// Return a successful DispatchResultWithPostInfo
 			Ok(().into())
 		}
 
+		/// Apply to be a manager without any role, `ManagerDeposit` is reserved
+		///
+		/// The roles should be granted by `modify_manager_roles` later.
+		///
+		/// Emits `ManagerAdded` and `Deposit` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn apply_manager(
+			origin: OriginFor<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			ensure!(!Managers::<T>::contains_key(who.clone()), Error::<T>::InUse);
+
+			// Reserve deposit.
+			let deposit = T::ManagerDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+
+			// Update manager.
+			Managers::<T>::insert(who.clone(), ManagerInfo {
+				deposit,
+				roles: ManagerRoles::default(),
+			});
+
+			// Emit event.
+			Self::deposit_event(Event::ManagerAdded(who.clone(), ManagerRoles::default()));
+			Self::deposit_event(Event::Deposit(who, deposit));
+			Ok(().into())
+		}
+
+		/// Reserve more deposit of the sender manager
+		///
+		/// - `amount`: The amount to reserve
+		///
+		/// Emits `Deposit` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn top_up_deposit(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			Managers::<T>::try_mutate(who.clone(), |maybe_manager| {
+				let info = maybe_manager.as_mut().ok_or(Error::<T>::NotManager)?;
+
+				T::Currency::reserve(&who, amount)?;
+				info.deposit = info.deposit.saturating_add(amount);
+
+				// Emit event.
+				Self::deposit_event(Event::Deposit(who.clone(), amount));
+				Ok(().into())
+			})
+		}
+
+		/// Unreserve some deposit of the sender manager, at least `ManagerDeposit` should be kept
+		///
+		/// - `amount`: The amount to unreserve
+		///
+		/// Emits `DepositWithdrawn` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn withdraw_deposit(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			Managers::<T>::try_mutate(who.clone(), |maybe_manager| {
+				let info = maybe_manager.as_mut().ok_or(Error::<T>::NotManager)?;
+
+				let rest = info.deposit.saturating_sub(amount);
+				ensure!(amount <= info.deposit && rest >= T::ManagerDeposit::get(), Error::<T>::DepositTooLow);
+
+				T::Currency::unreserve(&who, amount);
+				info.deposit = rest;
+
+				// Emit event.
+				Self::deposit_event(Event::DepositWithdrawn(who.clone(), amount));
+				Ok(().into())
+			})
+		}
+
+		/// Slash the deposit of some manager into the Nature account
+		///
+		/// - `who`: The manager of nature
+		/// - `amount`: The amount to slash, no more than the deposit
+		///
+		/// Emits `DepositSlashed` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn slash_deposit(
+			origin: OriginFor<T>,
+			who: <T::Lookup as StaticLookup>::Source,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			let one = T::Lookup::lookup(who)?;
+
+			let slashed = Self::slash_manager(&one, amount)?;
+
+			// Emit event.
+			Self::deposit_event(Event::DepositSlashed(one, slashed));
+			Ok(().into())
+		}
+
+		/// Modify the roles of some manager
+		///
+		/// The origin must be `ManagerOrigin`, or the manager itself which can only give up
+		/// some of its roles.
+		///
+		/// - `who`: The manager of nature
+		/// - `roles`: The new roles of the manager
+		///
+		/// Emits `ManagerRolesModified` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn modify_manager_roles(
+			origin: OriginFor<T>,
+			who: <T::Lookup as StaticLookup>::Source,
+			roles: ManagerRoles,
+		) -> DispatchResultWithPostInfo {
+			let maybe_self = match T::ManagerOrigin::try_origin(origin) {
+				Ok(_) => None,
+				Err(origin) => Some(ensure_signed(origin)?),
+			};
+			let one = T::Lookup::lookup(who)?;
+
+			Managers::<T>::try_mutate(one.clone(), |maybe_manager| {
+				let info = maybe_manager.as_mut().ok_or(Error::<T>::NotManager)?;
+
+				if let Some(sender) = maybe_self {
+					ensure!(sender == one, Error::<T>::NoPermission);
+					ensure!(roles.is_subset_of(&info.roles), Error::<T>::NoPermission);
+				}
+
+				let old_roles = sp_std::mem::replace(&mut info.roles, roles.clone());
+
+				// Emit event.
+				Self::deposit_event(Event::ManagerRolesModified(one.clone(), old_roles, roles));
+				Ok(().into())
+			})
+		}
+
 		/// Unset some manager
 		///
 		/// - `who`: The old manager of nature
@@ -134,6 +352,148 @@ pub mod pallet {
 			})
 		}
 
+		/// Propose a spend from the Nature account, the sender must be a treasurer
+		///
+		/// - `beneficiary`: The receiver of the spend
+		/// - `asset`: The native currency or featured asset to spend
+		///
+		/// Emits `SpendProposed` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(2), DispatchClass::Normal, Pays::No))]
+		pub(super) fn propose_spend(
+			origin: OriginFor<T>,
+			beneficiary: <T::Lookup as StaticLookup>::Source,
+			asset: SpendAssetOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_treasurer(&who), Error::<T>::NoPermission);
+			let beneficiary = T::Lookup::lookup(beneficiary)?;
+
+			let index = ProposalCount::<T>::get();
+			ProposalCount::<T>::put(index.saturating_add(1));
+			Proposals::<T>::insert(index, SpendProposal {
+				proposer: who.clone(),
+				beneficiary: beneficiary.clone(),
+				asset,
+			});
+
+			// Emit event.
+			Self::deposit_event(Event::SpendProposed(index, who, beneficiary));
+			Ok(().into())
+		}
+
+		/// Approve a spend proposal, it will be paid out in the next spend period
+		///
+		/// - `index`: The index of the proposal
+		///
+		/// Emits `SpendApproved` event when successful.
+		///
+		/// Weight: `O(A)` where `A` is the number of approvals
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn approve_spend(
+			origin: OriginFor<T>,
+			#[pallet::compact] index: ProposalIndex,
+		) -> DispatchResultWithPostInfo {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			ensure!(Proposals::<T>::contains_key(index), Error::<T>::InvalidProposal);
+
+			Approvals::<T>::try_mutate(|approvals| -> DispatchResult {
+				ensure!(!approvals.contains(&index), Error::<T>::AlreadyApproved);
+				approvals.push(index);
+				Ok(())
+			})?;
+
+			// Emit event.
+			Self::deposit_event(Event::SpendApproved(index));
+			Ok(().into())
+		}
+
+		/// Reject a spend proposal, approved or not
+		///
+		/// - `index`: The index of the proposal
+		///
+		/// Emits `SpendRejected` event when successful.
+		///
+		/// Weight: `O(A)` where `A` is the number of approvals
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(2), DispatchClass::Normal, Pays::No))]
+		pub(super) fn reject_spend(
+			origin: OriginFor<T>,
+			#[pallet::compact] index: ProposalIndex,
+		) -> DispatchResultWithPostInfo {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Proposals::<T>::take(index).ok_or(Error::<T>::InvalidProposal)?;
+			Approvals::<T>::mutate(|approvals| approvals.retain(|one| *one != index));
+
+			// Emit event.
+			Self::deposit_event(Event::SpendRejected(index));
+			Ok(().into())
+		}
+
+		/// Commit a random secret, the sender must be a dungeon server
+		///
+		/// An expired commitment which was never revealed is replaced, and `RevealMissedSlash`
+		/// is slashed from the deposit of sender.
+		///
+		/// - `commitment`: The hash of `(sender, secret)`
+		///
+		/// Emits `RandomCommitted` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+		pub(super) fn commit_random(
+			origin: OriginFor<T>,
+			commitment: T::Hash,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_server(&who), Error::<T>::NoPermission);
+
+			let now = frame_system::Module::<T>::block_number();
+			if let Some((_, committed_at)) = Commitments::<T>::get(&who) {
+				ensure!(now > committed_at.saturating_add(T::RevealPeriod::get()), Error::<T>::AlreadyCommitted);
+				let slashed = Self::slash_manager(&who, T::RevealMissedSlash::get())?;
+				Self::deposit_event(Event::RandomRevealMissed(who.clone(), slashed));
+			}
+			Commitments::<T>::insert(&who, (commitment, now));
+
+			// Emit event.
+			Self::deposit_event(Event::RandomCommitted(who, commitment));
+			Ok(().into())
+		}
+
+		/// Reveal the random secret committed by the sender, it is mixed into the random seed
+		///
+		/// The secret must be revealed after the block of commitment and in `RevealPeriod`.
+		///
+		/// - `secret`: The committed secret
+		///
+		/// Emits `RandomRevealed` event when successful.
+		///
+		/// Weight: `O(0)`
+		#[pallet::weight((10_000 + T::DbWeight::get().writes(2), DispatchClass::Normal, Pays::No))]
+		pub(super) fn reveal_random(
+			origin: OriginFor<T>,
+			secret: T::Hash,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			let (commitment, committed_at) = Commitments::<T>::get(&who).ok_or(Error::<T>::NotCommitted)?;
+			let now = frame_system::Module::<T>::block_number();
+			ensure!(now > committed_at, Error::<T>::RevealTooEarly);
+			ensure!(now <= committed_at.saturating_add(T::RevealPeriod::get()), Error::<T>::RevealExpired);
+			ensure!(T::Hashing::hash_of(&(&who, &secret)) == commitment, Error::<T>::InvalidReveal);
+
+			Commitments::<T>::remove(&who);
+			RevealedSeed::<T>::mutate(|(seed, revealed_at)| {
+				*seed = T::Hashing::hash_of(&(*seed, secret));
+				*revealed_at = now;
+			});
+
+			// Emit event.
+			Self::deposit_event(Event::RandomRevealed(who));
+			Ok(().into())
+		}
+
 	}
 
 	// The pallet's runtime storage items.
@@ -147,18 +507,98 @@ pub mod pallet {
 		ManagerInfo<BalanceOf<T>>
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn random_nonce)]
+	/// Number of random values generated in the current block.
+	pub(super) type RandomNonce<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn commitments)]
+	/// Random commitments of managers and the blocks they were made.
+	pub(super) type Commitments<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		(T::Hash, T::BlockNumber)
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn revealed_seed)]
+	/// The seed mixed from the secrets revealed since it was last used, and the block of the
+	/// last reveal.
+	pub(super) type RevealedSeed<T: Config> = StorageValue<_, (T::Hash, T::BlockNumber), ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	/// The storage layout version, used to run migrations.
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			// a new chain starts with the latest storage layout
+			StorageVersion::<T>::put(Releases::V2);
+		}
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn proposal_count)]
+	/// Number of spend proposals that have been made.
+	pub(super) type ProposalCount<T: Config> = StorageValue<_, ProposalIndex, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn proposals)]
+	/// Spend proposals that have been made.
+	pub(super) type Proposals<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		ProposalIndex,
+		SpendProposalOf<T>
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn approvals)]
+	/// Spend proposal indices that have been approved but not yet paid out.
+	pub(super) type Approvals<T: Config> = StorageValue<_, Vec<ProposalIndex>, ValueQuery>;
+
 	// Pallets use events to inform users when important changes are made.
 	// https://substrate.dev/docs/en/knowledgebase/runtime/events
 	#[pallet::event]
-	#[pallet::metadata(T::AccountId = "AccountId", T::Balance = "Balance")]
+	#[pallet::metadata(T::AccountId = "AccountId", T::Balance = "Balance", T::Hash = "Hash", SpendAssetOf<T> = "SpendAsset")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Manager was added. \[who\]
-		ManagerAdded(T::AccountId),
+		/// Manager was added. \[who, roles\]
+		ManagerAdded(T::AccountId, ManagerRoles),
+		/// Roles of manager were modified. \[who, old_roles, new_roles\]
+		ManagerRolesModified(T::AccountId, ManagerRoles, ManagerRoles),
 		/// Manager was removed. \[who\]
 		ManagerRemoved(T::AccountId),
-		/// Deposit Balance. \[amount\]
-		Deposit(T::Balance),
+		/// Deposit of manager was reserved. \[who, amount\]
+		Deposit(T::AccountId, BalanceOf<T>),
+		/// Deposit of manager was unreserved. \[who, amount\]
+		DepositWithdrawn(T::AccountId, BalanceOf<T>),
+		/// Deposit of manager was slashed into Nature account. \[who, amount\]
+		DepositSlashed(T::AccountId, BalanceOf<T>),
+		/// New spend proposal. \[index, proposer, beneficiary\]
+		SpendProposed(ProposalIndex, T::AccountId, T::AccountId),
+		/// Spend proposal was approved. \[index\]
+		SpendApproved(ProposalIndex),
+		/// Spend proposal was rejected. \[index\]
+		SpendRejected(ProposalIndex),
+		/// Spend proposal was paid out. \[index, beneficiary, asset\]
+		SpendPaid(ProposalIndex, T::AccountId, SpendAssetOf<T>),
+		/// Spend proposal could not be paid out, it stays approved. \[index\]
+		SpendDeferred(ProposalIndex),
+		/// Random secret was committed. \[who, commitment\]
+		RandomCommitted(T::AccountId, T::Hash),
+		/// Random secret was revealed and mixed into the seed. \[who\]
+		RandomRevealed(T::AccountId),
+		/// Random commitment expired without reveal, the deposit was slashed. \[who, slashed\]
+		RandomRevealMissed(T::AccountId, BalanceOf<T>),
 	}
 
 	#[deprecated(note = "use `Event` instead")]
@@ -174,6 +614,24 @@ pub mod pallet {
 		InUse,
 		/// The account id is not a manager.
 		NotManager,
+		/// The sender has no permission.
+		NoPermission,
+		/// The deposit would be less than `ManagerDeposit`.
+		DepositTooLow,
+		/// No proposal at that index.
+		InvalidProposal,
+		/// The proposal is already approved.
+		AlreadyApproved,
+		/// The sender has a commitment to reveal.
+		AlreadyCommitted,
+		/// The sender has no commitment.
+		NotCommitted,
+		/// The commitment can not be revealed in the block of commitment.
+		RevealTooEarly,
+		/// The `RevealPeriod` of the commitment is over.
+		RevealExpired,
+		/// The secret does not match the commitment.
+		InvalidReveal,
 	}
 }
 
@@ -183,12 +641,82 @@ pub struct ManagerInfo<
 > {
 	/// The balance.
 	deposit: Balance,
+	/// The roles of the account.
+	roles: ManagerRoles,
+}
+
+/// The storage layout versions of this pallet.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum Releases {
+	/// The roles are flags of `ManagerInfo`.
+	V1,
+	/// The roles are in `ManagerRoles`.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self { Self::V1 }
+}
+
+/// The roles of a manager
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default)]
+pub struct ManagerRoles {
 	/// Whether the account is an admin.
-	is_admin: bool,
+	pub is_admin: bool,
 	/// Whether the account is an issuer
-	is_issuer: bool,
+	pub is_issuer: bool,
 	/// Whether the account is an freezer
-	is_freezer: bool,
+	pub is_freezer: bool,
+	/// Whether the account is a dungeon server
+	pub is_server: bool,
+	/// Whether the account is a formula designer
+	pub is_designer: bool,
+	/// Whether the account is a treasurer
+	pub is_treasurer: bool,
+}
+
+impl ManagerRoles {
+	/// All the roles.
+	pub fn all() -> Self {
+		ManagerRoles {
+			is_admin: true,
+			is_issuer: true,
+			is_freezer: true,
+			is_server: true,
+			is_designer: true,
+			is_treasurer: true,
+		}
+	}
+
+	/// Whether all the roles are also in `other`.
+	pub fn is_subset_of(&self, other: &Self) -> bool {
+		(!self.is_admin || other.is_admin) &&
+		(!self.is_issuer || other.is_issuer) &&
+		(!self.is_freezer || other.is_freezer) &&
+		(!self.is_server || other.is_server) &&
+		(!self.is_designer || other.is_designer) &&
+		(!self.is_treasurer || other.is_treasurer)
+	}
+}
+
+/// The asset of a spend proposal
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum SpendAsset<Balance, AssetId, AssetBalance> {
+	/// Native currency
+	Native(Balance),
+	/// Some balance of a featured asset
+	Featured(AssetId, AssetBalance),
+}
+
+/// A spend proposal of the Nature account
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct SpendProposal<AccountId, SpendAsset> {
+	/// The treasurer who proposed the spend.
+	pub proposer: AccountId,
+	/// The account to whom the asset should be paid.
+	pub beneficiary: AccountId,
+	/// The asset to pay.
+	pub asset: SpendAsset,
 }
 
 // The main implementation block for the module.
@@ -202,6 +730,99 @@ impl<T: Config> Pallet<T> {
 	pub fn account_id() -> T::AccountId {
 		T::ModuleId::get().into_account()
 	}
+
+	/// Slash the deposit of a manager into the Nature account, no more than the deposit.
+	fn slash_manager(who: &T::AccountId, amount: BalanceOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+		Managers::<T>::try_mutate(who, |maybe_manager| {
+			let info = maybe_manager.as_mut().ok_or(Error::<T>::NotManager)?;
+
+			let amount = amount.min(info.deposit);
+			let remaining = T::Currency::repatriate_reserved(who, &Self::account_id(), amount, BalanceStatus::Free)?;
+			let slashed = amount.saturating_sub(remaining);
+			info.deposit = info.deposit.saturating_sub(slashed);
+			Ok(slashed)
+		})
+	}
+
+	/// Take the random nonce of the current block and increase it.
+	fn next_nonce() -> u32 {
+		RandomNonce::<T>::mutate(|nonce| {
+			let current = *nonce;
+			*nonce = nonce.wrapping_add(1);
+			current
+		})
+	}
+
+	/// A random hash of the subject, which differs on every call in a block.
+	fn random_hash(subject: &[u8]) -> T::Hash {
+		T::Randomness::random(&(T::ModuleId::get(), subject, Self::next_nonce()).encode())
+	}
+
+	/// A uniform random number in `[0, total)` drawn from the hashes of `next_hash`.
+	fn in_range(next_hash: impl Fn() -> T::Hash, total: u32) -> u32 {
+		if total <= 1 {
+			return 0;
+		}
+		// Only numbers below the largest multiple of `total` in the u32 space are uniform
+		// after the modulus operator, the others are rejected.
+		let space = u64::from(u32::MAX) + 1;
+		let limit = space - space % u64::from(total);
+
+		// Every u32 of a hash is a candidate, so running out of `MaxGenerateRandom` hashes
+		// is less likely than 2^-(8 * MaxGenerateRandom) for 32 bytes hashes.
+		let mut random_number = 0;
+		for _ in 0 .. T::MaxGenerateRandom::get().max(1) {
+			let random_seed = next_hash();
+			for mut chunk in random_seed.as_ref().chunks_exact(4) {
+				random_number = <u32>::decode(&mut chunk)
+					.expect("chunk has exactly 4 bytes; qed");
+				if u64::from(random_number) < limit {
+					return random_number % total;
+				}
+			}
+		}
+		random_number % total
+	}
+
+	/// Pay out all the approved spends, those can not be paid yet stay approved.
+	fn spend_funds() -> Weight {
+		let account = Self::account_id();
+		let approvals = Approvals::<T>::get();
+		let count = approvals.len() as Weight;
+
+		let mut deferred = Vec::new();
+		for index in approvals {
+			let proposal = match Proposals::<T>::get(index) {
+				Some(proposal) => proposal,
+				None => continue,
+			};
+			let paid = with_transaction(|| {
+				let result = match proposal.asset.clone() {
+					SpendAsset::Native(amount) => T::Currency::transfer(
+						&account, &proposal.beneficiary, amount, ExistenceRequirement::KeepAlive,
+					),
+					SpendAsset::Featured(asset_id, amount) => T::FeaturedAssets::transfer(
+						asset_id, &account, &proposal.beneficiary, amount,
+					).map(|_| ()).map_err(|e| e.error),
+				};
+				match result {
+					Ok(()) => TransactionOutcome::Commit(true),
+					Err(_) => TransactionOutcome::Rollback(false),
+				}
+			});
+
+			if paid {
+				Proposals::<T>::remove(index);
+				Self::deposit_event(Event::SpendPaid(index, proposal.beneficiary, proposal.asset));
+			} else {
+				deferred.push(index);
+				Self::deposit_event(Event::SpendDeferred(index));
+			}
+		}
+		Approvals::<T>::put(deferred);
+
+		T::DbWeight::get().reads_writes(1 + count * 3, 1 + count * 3)
+	}
 }
 
 impl<T: Config> ManagerAccessor<T::AccountId> for Pallet<T> {
@@ -210,46 +831,87 @@ impl<T: Config> ManagerAccessor<T::AccountId> for Pallet<T> {
 	}
 	/// Can thaw tokens, force transfers and burn tokens from any account.
 	fn is_admin(who: &T::AccountId) -> bool {
-		Managers::<T>::get(who).map(|x| x.is_admin).unwrap_or(false)
+		Managers::<T>::get(who).map(|x| x.roles.is_admin).unwrap_or(false)
 	}
 	/// Can mint tokens.
 	fn is_issuer(who: &T::AccountId) -> bool {
-		Managers::<T>::get(who).map(|x| x.is_issuer).unwrap_or(false)
+		Managers::<T>::get(who).map(|x| x.roles.is_issuer).unwrap_or(false)
 	}
 	/// Can freeze tokens.
 	fn is_freezer(who: &T::AccountId) -> bool {
-		Managers::<T>::get(who).map(|x| x.is_freezer).unwrap_or(false)
+		Managers::<T>::get(who).map(|x| x.roles.is_freezer).unwrap_or(false)
+	}
+	/// Can host dungeon instances.
+	fn is_server(who: &T::AccountId) -> bool {
+		Managers::<T>::get(who).map(|x| x.roles.is_server).unwrap_or(false)
+	}
+	/// Can design formulas.
+	fn is_designer(who: &T::AccountId) -> bool {
+		Managers::<T>::get(who).map(|x| x.roles.is_designer).unwrap_or(false)
+	}
+	/// Can propose treasury spends.
+	fn is_treasurer(who: &T::AccountId) -> bool {
+		Managers::<T>::get(who).map(|x| x.roles.is_treasurer).unwrap_or(false)
 	}
 }
 
 /// Generate a random hash
 impl<T: Config> RandomHash<T::Hash> for Pallet<T> {
-	fn generate() -> T::Hash {
-		T::Randomness::random(&(T::ModuleId::get(), 0).encode())
+	fn generate_by_subject(subject: &[u8]) -> T::Hash {
+		Self::random_hash(subject)
 	}
 }
 
 /// Generate a random number from a given seed.
 impl<T: Config> RandomNumber<u32> for Pallet<T> {
-	// Note that there is potential bias introduced by using modulus operator.
-	// You should call this function with different seed values until the random
-	// number lies within `u32::MAX - u32::MAX % n`.
 	fn generate_by_seed(seed: u32) -> u32 {
-		let random_seed = T::Randomness::random(&(T::ModuleId::get(), seed).encode());
+		let random_seed = Self::random_hash(&seed.encode());
 		let random_number = <u32>::decode(&mut random_seed.as_ref())
 			.expect("secure hashes should always be bigger than u32; qed");
 		random_number
 	}
-	fn generate_in_range(total: u32) -> u32 {
-		let mut random_number = Self::generate_by_seed(0);
+	fn generate_in_range_by_subject(subject: &[u8], total: u32) -> u32 {
+		Self::in_range(|| Self::random_hash(subject), total)
+	}
+}
 
-		// Best effort attempt to remove bias from modulus operator.
-		for i in 1 .. T::MaxGenerateRandom::get() {
-			if random_number < u32::MAX - u32::MAX % total {
-				break;
-			}
-			random_number = Self::generate_by_seed(i);
+/// The random source mixed from the secrets revealed by servers.
+///
+/// The revealed seed is public, so the values are derived from the seed mixed with the
+/// `Randomness` of Nature for the subject and the random nonce. Every revealed seed is used
+/// by only one value, then the next reveal starts a new seed. If no unused secret was revealed
+/// in the last `RevealPeriod`, it falls back to the `Randomness` of Nature only.
+pub struct CommitReveal<T>(PhantomData<T>);
+
+impl<T: Config> CommitReveal<T> {
+	/// Take the revealed seed if it is still fresh, so that it is never used again.
+	fn take_fresh_seed() -> Option<T::Hash> {
+		let (seed, revealed_at) = RevealedSeed::<T>::get();
+		let now = frame_system::Module::<T>::block_number();
+		if revealed_at.is_zero() || now > revealed_at.saturating_add(T::RevealPeriod::get()) {
+			return None;
 		}
-		random_number % total
+		RevealedSeed::<T>::kill();
+		Some(seed)
+	}
+}
+
+impl<T: Config> RandomHash<T::Hash> for CommitReveal<T> {
+	fn generate_by_subject(subject: &[u8]) -> T::Hash {
+		match Self::take_fresh_seed() {
+			Some(seed) => T::Hashing::hash_of(&(seed, Pallet::<T>::random_hash(subject))),
+			None => Pallet::<T>::random_hash(subject),
+		}
+	}
+}
+
+impl<T: Config> RandomNumber<u32> for CommitReveal<T> {
+	fn generate_by_seed(seed: u32) -> u32 {
+		let random_seed = Self::generate_by_subject(&seed.encode());
+		<u32>::decode(&mut random_seed.as_ref())
+			.expect("secure hashes should always be bigger than u32; qed")
+	}
+	fn generate_in_range_by_subject(subject: &[u8], total: u32) -> u32 {
+		Pallet::<T>::in_range(|| Self::generate_by_subject(subject), total)
 	}
 }
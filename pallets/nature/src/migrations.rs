@@ -0,0 +1,34 @@
+//! Storage migrations of the Nature pallet.
+
+use super::*;
+
+/// The manager info before `Releases::V2`, the deposit was never reserved.
+#[derive(Decode)]
+struct OldManagerInfo<Balance> {
+	_deposit: Balance,
+	is_admin: bool,
+	is_issuer: bool,
+	is_freezer: bool,
+}
+
+/// Move the role flags of every manager into `ManagerRoles`.
+///
+/// The deposit is reset to zero, the recorded amount is not reserved by this pallet and must
+/// not be unreserved or slashed later.
+pub fn migrate_to_roles<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+	Managers::<T>::translate::<OldManagerInfo<BalanceOf<T>>, _>(|_, old| {
+		translated = translated.saturating_add(1);
+		Some(ManagerInfo {
+			deposit: Zero::zero(),
+			roles: ManagerRoles {
+				is_admin: old.is_admin,
+				is_issuer: old.is_issuer,
+				is_freezer: old.is_freezer,
+				..Default::default()
+			},
+		})
+	});
+
+	T::DbWeight::get().reads_writes(translated, translated)
+}
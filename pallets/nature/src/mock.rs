@@ -0,0 +1,137 @@
+use crate as mc_nature;
+use frame_support::{parameter_types, traits::{GenesisBuild, Randomness}};
+use frame_system::{EnsureOneOf, EnsureRoot};
+use mc_support::traits::{FreezerRole, IssuerRole};
+use sp_core::H256;
+use sp_runtime::{
+	ModuleId,
+	testing::Header,
+	traits::{BlakeTwo256, Hash, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		Assets: mc_featured_assets::{Module, Call, Storage, Event<T>},
+		Nature: mc_nature::{Module, Call, Storage, Config, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const AssetDepositBase: u64 = 1;
+	pub const AssetDepositPerZombie: u64 = 1;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u64 = 1;
+	pub const MetadataDepositPerByte: u64 = 1;
+}
+
+impl mc_featured_assets::Config for Test {
+	type Currency = Balances;
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type ForceOrigin = EnsureRoot<u64>;
+	type ManagerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, mc_nature::EnsureManager<Test>>;
+	type IssuerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, mc_nature::EnsureManager<Test, IssuerRole>>;
+	type FreezerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, mc_nature::EnsureManager<Test, FreezerRole>>;
+	type AssetDepositBase = AssetDepositBase;
+	type AssetDepositPerZombie = AssetDepositPerZombie;
+	type StringLimit = StringLimit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type WeightInfo = ();
+	type AssetAdmin = Nature;
+	type RandomNumber = Nature;
+}
+
+pub struct TestRandomness;
+impl Randomness<H256> for TestRandomness {
+	fn random(subject: &[u8]) -> H256 { BlakeTwo256::hash(subject) }
+}
+
+parameter_types! {
+	pub const NatureModuleId: ModuleId = ModuleId(*b"mc/natur");
+	pub const MaxGenerateRandom: u32 = 10;
+	pub const ManagerDeposit: u64 = 10;
+	pub const SpendPeriod: u64 = 5;
+	pub const RevealPeriod: u64 = 5;
+	pub const RevealMissedSlash: u64 = 4;
+}
+
+impl mc_nature::Config for Test {
+	type Event = Event;
+	type ModuleId = NatureModuleId;
+	type Balance = u64;
+	type Currency = Balances;
+	type ManagerOrigin = EnsureOneOf<u64, EnsureRoot<u64>, mc_nature::EnsureManager<Test>>;
+	type ManagerDeposit = ManagerDeposit;
+	type FeaturedAssets = Assets;
+	type SpendPeriod = SpendPeriod;
+	type RevealPeriod = RevealPeriod;
+	type RevealMissedSlash = RevealMissedSlash;
+	type Randomness = TestRandomness;
+	type MaxGenerateRandom = MaxGenerateRandom;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100), (4, 5)],
+	}.assimilate_storage(&mut t).unwrap();
+	GenesisBuild::<Test>::assimilate_storage(&mc_nature::GenesisConfig::default(), &mut t).unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,404 @@
+// Tests to be written here
+
+use crate::mock::*;
+use crate::*;
+use frame_support::{
+	assert_noop, assert_ok, error::BadOrigin, Blake2_128Concat, StorageHasher,
+	storage::migration::put_storage_value,
+	traits::{Currency, EnsureOrigin, OnInitialize, OnRuntimeUpgrade, Randomness},
+};
+use codec::Encode;
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use mc_support::traits::{ServerRole, TreasurerRole, RandomHash, RandomNumber};
+
+fn server_roles() -> ManagerRoles {
+	ManagerRoles { is_server: true, is_designer: true, ..Default::default() }
+}
+
+#[test]
+fn set_manager_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::set_manager(Origin::root(), 1, ManagerRoles::all()));
+		assert!(Nature::is_admin(&1));
+		assert!(Nature::is_treasurer(&1));
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		assert_noop!(Nature::set_manager(Origin::root(), 1, ManagerRoles::all()), Error::<Test>::InUse);
+
+		// an admin manager is also the manager origin
+		assert_ok!(Nature::set_manager(Origin::signed(1), 2, server_roles()));
+		assert!(!Nature::is_admin(&2));
+		assert!(Nature::is_server(&2));
+		assert_noop!(Nature::set_manager(Origin::signed(2), 3, server_roles()), BadOrigin);
+		assert_noop!(Nature::set_manager(Origin::signed(3), 3, server_roles()), BadOrigin);
+	});
+}
+
+#[test]
+fn modify_manager_roles_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+
+		let new_roles = ManagerRoles { is_treasurer: true, ..Default::default() };
+		assert_ok!(Nature::modify_manager_roles(Origin::root(), 2, new_roles.clone()));
+		assert!(!Nature::is_server(&2));
+		assert!(Nature::is_treasurer(&2));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			crate::mock::Event::mc_nature(crate::Event::ManagerRolesModified(2, server_roles(), new_roles)),
+		);
+
+		assert_noop!(
+			Nature::modify_manager_roles(Origin::root(), 3, ManagerRoles::all()),
+			Error::<Test>::NotManager
+		);
+	});
+}
+
+#[test]
+fn manager_can_only_give_up_own_roles() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+		assert_ok!(Nature::set_manager(Origin::root(), 3, server_roles()));
+
+		assert_noop!(
+			Nature::modify_manager_roles(Origin::signed(2), 2, ManagerRoles::all()),
+			Error::<Test>::NoPermission
+		);
+		assert_noop!(
+			Nature::modify_manager_roles(Origin::signed(3), 2, ManagerRoles::default()),
+			Error::<Test>::NoPermission
+		);
+
+		let new_roles = ManagerRoles { is_server: true, ..Default::default() };
+		assert_ok!(Nature::modify_manager_roles(Origin::signed(2), 2, new_roles));
+		assert!(Nature::is_server(&2));
+		assert!(!Nature::is_designer(&2));
+	});
+}
+
+#[test]
+fn ensure_manager_should_check_role() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+
+		assert_eq!(EnsureManager::<Test, ServerRole>::try_origin(Origin::signed(2)).ok(), Some(2));
+		assert!(EnsureManager::<Test, TreasurerRole>::try_origin(Origin::signed(2)).is_err());
+		assert!(EnsureManager::<Test>::try_origin(Origin::signed(2)).is_err());
+		assert!(EnsureManager::<Test, ServerRole>::try_origin(Origin::root()).is_err());
+	});
+}
+
+#[test]
+fn set_manager_without_deposit_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert!(Nature::set_manager(Origin::root(), 4, ManagerRoles::all()).is_err());
+		assert_eq!(Nature::managers(4), None);
+	});
+}
+
+#[test]
+fn apply_manager_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::apply_manager(Origin::signed(2)));
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		assert_eq!(Nature::managers(2).unwrap().roles, ManagerRoles::default());
+		assert!(!Nature::is_admin(&2));
+		assert_noop!(Nature::apply_manager(Origin::signed(2)), Error::<Test>::InUse);
+
+		assert_ok!(Nature::unset_manager(Origin::root(), 2));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+	});
+}
+
+#[test]
+fn top_up_and_withdraw_deposit_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Nature::top_up_deposit(Origin::signed(2), 5), Error::<Test>::NotManager);
+		assert_ok!(Nature::apply_manager(Origin::signed(2)));
+
+		assert_ok!(Nature::top_up_deposit(Origin::signed(2), 15));
+		assert_eq!(Nature::managers(2).unwrap().deposit, 25);
+		assert_eq!(Balances::reserved_balance(&2), 25);
+
+		assert_noop!(Nature::withdraw_deposit(Origin::signed(2), 16), Error::<Test>::DepositTooLow);
+		assert_ok!(Nature::withdraw_deposit(Origin::signed(2), 15));
+		assert_eq!(Nature::managers(2).unwrap().deposit, 10);
+		assert_eq!(Balances::free_balance(&2), 90);
+	});
+}
+
+#[test]
+fn slash_deposit_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::apply_manager(Origin::signed(2)));
+		assert_noop!(Nature::slash_deposit(Origin::signed(2), 2, 5), BadOrigin);
+
+		assert_ok!(Nature::slash_deposit(Origin::root(), 2, 4));
+		assert_eq!(Nature::managers(2).unwrap().deposit, 6);
+		assert_eq!(Balances::free_balance(&Nature::account_id()), 4);
+
+		// no more than the deposit
+		assert_ok!(Nature::slash_deposit(Origin::root(), 2, 100));
+		assert_eq!(Nature::managers(2).unwrap().deposit, 0);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&Nature::account_id()), 10);
+	});
+}
+
+fn treasurer_roles() -> ManagerRoles {
+	ManagerRoles { is_treasurer: true, ..Default::default() }
+}
+
+#[test]
+fn propose_spend_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::set_manager(Origin::root(), 1, treasurer_roles()));
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+
+		assert_noop!(Nature::propose_spend(Origin::signed(2), 3, SpendAsset::Native(30)), Error::<Test>::NoPermission);
+		assert_ok!(Nature::propose_spend(Origin::signed(1), 3, SpendAsset::Native(30)));
+		assert_eq!(Nature::proposal_count(), 1);
+		assert_eq!(Nature::proposals(0), Some(SpendProposal {
+			proposer: 1,
+			beneficiary: 3,
+			asset: SpendAsset::Native(30),
+		}));
+		assert!(Nature::approvals().is_empty());
+	});
+}
+
+#[test]
+fn approve_and_reject_spend_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nature::set_manager(Origin::root(), 1, treasurer_roles()));
+		assert_ok!(Nature::propose_spend(Origin::signed(1), 3, SpendAsset::Native(30)));
+
+		// a treasurer can not approve
+		assert_noop!(Nature::approve_spend(Origin::signed(1), 0), BadOrigin);
+		assert_noop!(Nature::approve_spend(Origin::root(), 1), Error::<Test>::InvalidProposal);
+		assert_ok!(Nature::approve_spend(Origin::root(), 0));
+		assert_eq!(Nature::approvals(), vec![0]);
+		assert_noop!(Nature::approve_spend(Origin::root(), 0), Error::<Test>::AlreadyApproved);
+
+		assert_ok!(Nature::reject_spend(Origin::root(), 0));
+		assert_eq!(Nature::proposals(0), None);
+		assert!(Nature::approvals().is_empty());
+		assert_noop!(Nature::reject_spend(Origin::root(), 0), Error::<Test>::InvalidProposal);
+	});
+}
+
+#[test]
+fn approved_spends_should_be_paid_out_each_period() {
+	new_test_ext().execute_with(|| {
+		let treasury = Nature::account_id();
+		Balances::make_free_balance_be(&treasury, 50);
+		assert_ok!(Assets::force_create(Origin::root(), 0, 1, 10, 1));
+		assert_ok!(Assets::mint(Origin::root(), 0, treasury, 20));
+
+		assert_ok!(Nature::set_manager(Origin::root(), 1, treasurer_roles()));
+		assert_ok!(Nature::propose_spend(Origin::signed(1), 3, SpendAsset::Native(30)));
+		assert_ok!(Nature::propose_spend(Origin::signed(1), 4, SpendAsset::Featured(0, 15)));
+		assert_ok!(Nature::propose_spend(Origin::signed(1), 3, SpendAsset::Native(100)));
+		assert_ok!(Nature::propose_spend(Origin::signed(1), 2, SpendAsset::Native(1)));
+		for index in 0..3 {
+			assert_ok!(Nature::approve_spend(Origin::root(), index));
+		}
+
+		// nothing is paid before the spend period
+		Nature::on_initialize(4);
+		assert_eq!(Balances::free_balance(&3), 100);
+
+		Nature::on_initialize(5);
+		assert_eq!(Balances::free_balance(&3), 130);
+		assert_eq!(Balances::free_balance(&treasury), 20);
+		assert_eq!(Assets::balance(0, 4), 15);
+		assert_eq!(Assets::balance(0, treasury), 5);
+		assert_eq!(Nature::proposals(0), None);
+		assert_eq!(Nature::proposals(1), None);
+		assert!(System::events().iter().any(|record| record.event ==
+			crate::mock::Event::mc_nature(crate::Event::SpendPaid(1, 4, SpendAsset::Featured(0, 15)))
+		));
+
+		// the unaffordable spend is deferred, the unapproved one is untouched
+		assert_eq!(Nature::approvals(), vec![2]);
+		assert!(Nature::proposals(3).is_some());
+		assert!(System::events().iter().any(|record| record.event ==
+			crate::mock::Event::mc_nature(crate::Event::SpendDeferred(2))
+		));
+	});
+}
+
+#[test]
+fn random_values_should_differ_in_block() {
+	new_test_ext().execute_with(|| {
+		let first = <Nature as RandomHash<_>>::generate();
+		assert_ne!(<Nature as RandomHash<_>>::generate(), first);
+		assert_ne!(Nature::generate_by_subject(b"craft"), Nature::generate_by_subject(b"craft"));
+
+		let first = Nature::generate_by_seed(0);
+		assert_ne!(Nature::generate_by_seed(0), first);
+
+		let numbers: Vec<u32> = (0..10).map(|_| Nature::generate_in_range(1_000_000)).collect();
+		assert!(numbers.iter().all(|n| *n < 1_000_000));
+		assert!(numbers.iter().skip(1).any(|n| *n != numbers[0]));
+		assert!(Nature::random_nonce() >= 14);
+	});
+}
+
+#[test]
+fn random_nonce_should_reset_each_block() {
+	new_test_ext().execute_with(|| {
+		let first = Nature::generate_in_range_by_subject(b"roll", u32::MAX);
+		let second = Nature::generate_in_range_by_subject(b"roll", u32::MAX);
+		assert_ne!(first, second);
+
+		// the same nonces in another block repeat the values of the mocked randomness
+		Nature::on_initialize(2);
+		assert_eq!(Nature::random_nonce(), 0);
+		assert_eq!(Nature::generate_in_range_by_subject(b"roll", u32::MAX), first);
+	});
+}
+
+#[test]
+fn random_number_in_small_range_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Nature::generate_in_range(0), 0);
+		assert_eq!(Nature::generate_in_range(1), 0);
+		assert_eq!(Nature::random_nonce(), 0);
+		for _ in 0..20 {
+			assert!(Nature::generate_in_range(3) < 3);
+		}
+	});
+}
+
+fn commitment(who: u64, secret: H256) -> H256 {
+	BlakeTwo256::hash_of(&(who, secret))
+}
+
+#[test]
+fn commit_and_reveal_random_should_work() {
+	new_test_ext().execute_with(|| {
+		let secret = H256::repeat_byte(7);
+		assert_noop!(Nature::commit_random(Origin::signed(2), commitment(2, secret)), Error::<Test>::NoPermission);
+		// only servers can commit
+		assert_ok!(Nature::set_manager(Origin::root(), 3, ManagerRoles { is_admin: true, ..Default::default() }));
+		assert_noop!(Nature::commit_random(Origin::signed(3), commitment(3, secret)), Error::<Test>::NoPermission);
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+		assert_ok!(Nature::commit_random(Origin::signed(2), commitment(2, secret)));
+		assert_noop!(Nature::commit_random(Origin::signed(2), commitment(2, secret)), Error::<Test>::AlreadyCommitted);
+
+		assert_noop!(Nature::reveal_random(Origin::signed(2), secret), Error::<Test>::RevealTooEarly);
+		System::set_block_number(2);
+		assert_noop!(Nature::reveal_random(Origin::signed(3), secret), Error::<Test>::NotCommitted);
+		assert_noop!(Nature::reveal_random(Origin::signed(2), H256::zero()), Error::<Test>::InvalidReveal);
+		assert_ok!(Nature::reveal_random(Origin::signed(2), secret));
+		assert_eq!(Nature::commitments(2), None);
+		assert_eq!(Nature::revealed_seed(), (BlakeTwo256::hash_of(&(H256::zero(), secret)), 2));
+	});
+}
+
+#[test]
+fn missed_reveal_should_expire() {
+	new_test_ext().execute_with(|| {
+		let secret = H256::repeat_byte(7);
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+		assert_ok!(Nature::commit_random(Origin::signed(2), commitment(2, secret)));
+
+		System::set_block_number(7);
+		assert_noop!(Nature::reveal_random(Origin::signed(2), secret), Error::<Test>::RevealExpired);
+
+		// a new commitment replaces the expired one, the deposit is slashed
+		assert_ok!(Nature::commit_random(Origin::signed(2), commitment(2, H256::zero())));
+		assert_eq!(Nature::commitments(2), Some((commitment(2, H256::zero()), 7)));
+		assert!(System::events().iter().any(|record| record.event ==
+			crate::mock::Event::mc_nature(crate::Event::RandomRevealMissed(2, 4))
+		));
+		assert_eq!(Nature::managers(2).unwrap().deposit, 6);
+		assert_eq!(Balances::reserved_balance(&2), 6);
+		assert_eq!(Balances::free_balance(&Nature::account_id()), 4);
+	});
+}
+
+#[test]
+fn commit_reveal_should_fall_back_without_fresh_seed() {
+	new_test_ext().execute_with(|| {
+		// no secret revealed, the same as the randomness of Nature
+		let fallback = CommitReveal::<Test>::generate_by_subject(b"loot");
+		Nature::on_initialize(1);
+		assert_eq!(Nature::generate_by_subject(b"loot"), fallback);
+
+		let secret = H256::repeat_byte(7);
+		assert_ok!(Nature::set_manager(Origin::root(), 2, server_roles()));
+		assert_ok!(Nature::commit_random(Origin::signed(2), commitment(2, secret)));
+		System::set_block_number(2);
+		assert_ok!(Nature::reveal_random(Origin::signed(2), secret));
+
+		// the revealed seed is mixed with the randomness while it is fresh
+		Nature::on_initialize(2);
+		let (seed, _) = Nature::revealed_seed();
+		let mixed = CommitReveal::<Test>::generate_by_subject(b"loot");
+		let randomness = TestRandomness::random(&(NatureModuleId::get(), &b"loot"[..], 0u32).encode());
+		assert_eq!(mixed, BlakeTwo256::hash_of(&(seed, randomness)));
+		assert_ne!(mixed, fallback);
+		// the seed is used only once
+		assert_eq!(Nature::revealed_seed(), (H256::zero(), 0));
+		assert_eq!(
+			CommitReveal::<Test>::generate_by_subject(b"loot"),
+			TestRandomness::random(&(NatureModuleId::get(), &b"loot"[..], 1u32).encode())
+		);
+		assert!(CommitReveal::<Test>::generate_in_range(6) < 6);
+
+		// the next reveal starts a new seed
+		assert_ok!(Nature::commit_random(Origin::signed(2), commitment(2, H256::zero())));
+		System::set_block_number(3);
+		assert_ok!(Nature::reveal_random(Origin::signed(2), H256::zero()));
+		assert_eq!(Nature::revealed_seed(), (BlakeTwo256::hash_of(&(H256::zero(), H256::zero())), 3));
+
+		System::set_block_number(9);
+		Nature::on_initialize(9);
+		assert_eq!(CommitReveal::<Test>::generate_by_subject(b"loot"), fallback);
+	});
+}
+
+#[test]
+fn runtime_upgrade_should_do_nothing_on_new_chain() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Nature::storage_version(), Releases::V2);
+		assert_ok!(Nature::set_manager(Origin::root(), 1, server_roles()));
+
+		Nature::on_runtime_upgrade();
+		assert_eq!(Nature::storage_version(), Releases::V2);
+		assert!(Nature::is_server(&1));
+		assert!(!Nature::is_admin(&1));
+	});
+}
+
+#[test]
+fn migrate_to_roles_should_work() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V1);
+		// reserved by other pallets
+		assert_ok!(Balances::reserve(&1, 50));
+		// deposit, is_admin, is_issuer, is_freezer
+		put_storage_value(b"Nature", b"Managers", &Blake2_128Concat::hash(&1u64.encode()), (50u64, true, false, true));
+		put_storage_value(b"Nature", b"Managers", &Blake2_128Concat::hash(&2u64.encode()), (0u64, false, true, false));
+
+		Nature::on_runtime_upgrade();
+		assert_eq!(Nature::storage_version(), Releases::V2);
+		assert_eq!(
+			Nature::managers(1),
+			Some(ManagerInfo {
+				deposit: 0,
+				roles: ManagerRoles { is_admin: true, is_freezer: true, ..Default::default() },
+			})
+		);
+		assert!(Nature::is_issuer(&2));
+		assert!(!Nature::is_admin(&2));
+		// can be removed without deposit
+		assert_ok!(Nature::unset_manager(Origin::root(), 2));
+		// the reserves of other pallets are kept
+		assert_ok!(Nature::unset_manager(Origin::root(), 1));
+		assert_eq!(Balances::reserved_balance(&1), 50);
+	});
+}
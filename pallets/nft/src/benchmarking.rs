@@ -0,0 +1,74 @@
+//! Benchmarks of the commodities pallet, the cost of each call should not
+//! depend on the number of commodities already owned by the account.
+
+use super::*;
+use frame_benchmarking::{benchmarks, account, whitelisted_caller, impl_benchmark_test_suite};
+use frame_support::traits::EnsureOrigin;
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+
+/// Give `who` a number of commodities directly in storage.
+fn own_commodities<T: Config>(who: &T::AccountId, n: u32) {
+	for i in 0 .. n {
+		let commodity_id = T::Hashing::hash_of(&(b"bench", i));
+		OwnedCommodities::<T>::insert(who, commodity_id, ());
+		CommodityInfos::<T>::insert(commodity_id, T::CommodityInfo::default());
+		AccountForCommodity::<T>::insert(commodity_id, who);
+	}
+}
+
+/// Create a collection without limits, owned and issued by `who`.
+fn create_collection<T: Config>(who: &T::AccountId) -> CollectionId {
+	Pallet::<T>::insert_collection(who.clone(), who.clone(), u128::max_value(), u64::max_value())
+		.expect("collection IDs are not used up; qed")
+}
+
+/// Mint the default commodity to `who` through the pallet.
+fn mint_commodity<T: Config>(who: &T::AccountId) -> T::Hash {
+	let collection_id = create_collection::<T>(who);
+	<Pallet<T> as UniqueAssets<_>>::mint(collection_id, who, T::CommodityInfo::default())
+		.expect("the default commodity does not exist yet; qed")
+}
+
+benchmarks! {
+	mint {
+		let n in 0 .. 1000;
+		let owner: T::AccountId = account("owner", 0, SEED);
+		own_commodities::<T>(&owner, n);
+		let collection_id = create_collection::<T>(&owner);
+		let origin = T::CommodityAdmin::successful_origin();
+	}: _<T::Origin>(origin, collection_id, owner.clone(), T::CommodityInfo::default())
+	verify {
+		let commodity_id = T::Hashing::hash_of(&(collection_id, T::CommodityInfo::default()));
+		assert_eq!(AccountForCommodity::<T>::get(commodity_id), owner);
+	}
+
+	burn {
+		let n in 0 .. 1000;
+		let caller: T::AccountId = whitelisted_caller();
+		own_commodities::<T>(&caller, n);
+		let commodity_id = mint_commodity::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), commodity_id)
+	verify {
+		assert!(!OwnedCommodities::<T>::contains_key(&caller, commodity_id));
+	}
+
+	transfer {
+		let n in 0 .. 1000;
+		let caller: T::AccountId = whitelisted_caller();
+		let dest: T::AccountId = account("dest", 0, SEED);
+		own_commodities::<T>(&caller, n);
+		own_commodities::<T>(&dest, n);
+		let commodity_id = mint_commodity::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), dest.clone(), commodity_id)
+	verify {
+		assert!(OwnedCommodities::<T>::contains_key(&dest, commodity_id));
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(),
+	crate::mock::Test,
+);
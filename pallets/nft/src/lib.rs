@@ -14,8 +14,12 @@
 //! by an asset admin. Asset owners may burn assets or transfer their
 //! ownership. Configuration parameters are used to limit the total number of a
 //! type of asset that may exist as well as the number that any one account may
-//! own. Assets are uniquely identified by the hash of the info that defines
-//! them, as calculated by the runtime system's hashing algorithm.
+//! own. Assets are uniquely identified by the hash of their collection and the
+//! info that defines them, as calculated by the runtime system's hashing algorithm.
+//!
+//! Assets are grouped in collections created by the asset admin. Each collection
+//! has an owner who manages it, an issuer who mints into it, its own metadata and
+//! its own limits of supply and of the number that any one account may own.
 //!
 //! This pallet implements the [`UniqueAssets`](./nft/trait.UniqueAssets.html)
 //! trait in a way that is optimized for assets that are expected to be traded
@@ -23,9 +27,18 @@
 //!
 //! ### Dispatchable Functions
 //!
+//! * [`create_collection`](./enum.Call.html#variant.create_collection) - Create
+//!   a new collection of commodities. May only be called by the commodity admin.
+//!
+//! * [`set_collection_issuer`](./enum.Call.html#variant.set_collection_issuer) -
+//!   Change the issuer of a collection. May only be called by collection owner.
+//!
+//! * [`add_collection_meta`](./enum.Call.html#variant.add_collection_meta) - Add
+//!   meta for a collection. May only be called by collection owner.
+//!
 //! * [`mint`](./enum.Call.html#variant.mint) - Use the provided commodity info
-//!   to create a new commodity for the specified user. May only be called by
-//!   the commodity admin.
+//!   to create a new commodity in a collection for the specified user. May only
+//!   be called by the commodity admin or the collection issuer.
 //!
 //! * [`burn`](./enum.Call.html#variant.burn) - Destroy a commodity. May only be
 //!   called by commodity owner.
@@ -36,27 +49,35 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::{Decode, Encode,FullCodec};
+use codec::{Decode, Encode, EncodeLike, FullCodec};
 use sp_std::{cmp::Eq, fmt::Debug, vec::Vec};
 use sp_runtime::{
-    traits::{Hash},
+    traits::{Hash, Zero, One, Saturating, UniqueSaturatedInto},
     RuntimeDebug, DispatchResult, DispatchError,
 };
 use frame_support::{
 	ensure,
-	traits::{Get},
+	traits::{Get, Currency, ReservableCurrency},
+	weights::Weight,
 	Hashable,
 };
 use mc_support::traits::{
-	LifeTime, UniqueAssets
+	LifeTime, UniqueAssets, UniqueAssetLock, FeaturedAssets,
 };
 
+pub mod migrations;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
+pub use weights::WeightInfo;
 pub use pallet::*;
 
 #[frame_support::pallet]
@@ -83,44 +104,212 @@ pub mod pallet {
 		type CommodityAdmin: EnsureOrigin<Self::Origin>;
 		/// The data type that is used to describe this type of commodity.
 		type CommodityInfo: Hashable + Member + Debug + Default + FullCodec + Ord;
+		/// The data type that described this type of commodity before `Releases::V2`, it is
+		/// translated into `CommodityInfo` by the migration.
+		type OldCommodityInfo: Decode + Into<Self::CommodityInfo>;
 		/// The maximum number of this type of commodity that may exist (minted - burned).
 		type CommodityLimit: Get<u128>;
 		/// The maximum number of this type of commodity that any single account may own.
 		type UserCommodityLimit: Get<u64>;
 		/// The decay time in block number delta
 		type LifeTime: LifeTime<Self::BlockNumber>;
+		/// The lock of commodities, e.g. the equipments of actors
+		type AssetLock: UniqueAssetLock<Self::AccountId, Self::Hash>;
+		/// What happens to a commodity when it decays
+		type DecayPolicy: Get<DecayPolicy>;
+		/// The maximum number of commodities decayed in a block, the others are delayed
+		type MaxDecaysPerBlock: Get<u32>;
+		/// The featured assets burned to extend the life of commodities
+		type FeaturedAssets: FeaturedAssets<Self::AccountId>;
+		/// The blocks of life extended by burning one unit of featured asset
+		type LifePerAsset: Get<Self::BlockNumber>;
+		/// The currency reserved for the deposits of metadata
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The basic amount of funds that must be reserved for a metadata entry
+		type MetadataDepositBase: Get<BalanceOf<Self>>;
+		/// The additional funds that must be reserved for the number of bytes of a metadata entry
+		type MetadataDepositPerByte: Get<BalanceOf<Self>>;
+		/// The maximum length of a metadata key
+		type KeyLimit: Get<u32>;
+		/// The maximum length of a bytes metadata value
+		type ValueLimit: Get<u32>;
+		/// The maximum number of metadata entries of a commodity or a collection
+		type MetadataLimit: Get<u32>;
+		/// The owner and issuer of the legacy collection created by the migration
+		type LegacyCollectionOwner: Get<Self::AccountId>;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		// TODO handle dying actors
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() == Releases::V1 {
+				weight = weight.saturating_add(migrations::migrate_to_category::<T>());
+				StorageVersion::<T>::put(Releases::V2);
+			}
+			if StorageVersion::<T>::get() == Releases::V2 {
+				weight = weight.saturating_add(migrations::migrate_to_double_map::<T>());
+				StorageVersion::<T>::put(Releases::V3);
+			}
+			if StorageVersion::<T>::get() == Releases::V3 {
+				weight = weight.saturating_add(migrations::migrate_to_collections::<T>());
+				StorageVersion::<T>::put(Releases::V4);
+			}
+			if StorageVersion::<T>::get() == Releases::V4 {
+				weight = weight.saturating_add(migrations::migrate_to_typed_meta::<T>());
+				StorageVersion::<T>::put(Releases::V5);
+			}
+			if StorageVersion::<T>::get() == Releases::V5 {
+				weight = weight.saturating_add(migrations::migrate_to_decay_queue::<T>());
+				StorageVersion::<T>::put(Releases::V6);
+			}
+			weight
+		}
+
+		/// decay the commodities whose life is over
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut decaying = DecayQueue::<T>::take(now).unwrap_or_default();
+			// the rest of queue is delayed to the next block
+			let limit = T::MaxDecaysPerBlock::get() as usize;
+			if decaying.len() > limit {
+				let rest = decaying.split_off(limit);
+				DecayQueue::<T>::mutate(now.saturating_add(One::one()), |maybe_list| {
+					maybe_list.get_or_insert_with(Vec::new).extend(rest);
+				});
+			}
+			for commodity_id in decaying.iter() {
+				Self::try_decay(commodity_id, now);
+			}
+			let count = decaying.len() as Weight;
+			T::DbWeight::get().reads_writes(2 + count * 3, 2 + count * 6)
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-        /// Create a new commodity from the provided commodity info and identify the specified
-        /// account as its owner. The ID of the new commodity will be equal to the hash of the info
-        /// that defines it, as calculated by the runtime system's hashing algorithm.
+        /// Create a new collection of commodities.
         ///
         /// The dispatch origin for this call must be the commodity admin.
         ///
+        /// - `owner`: The account who manages the collection.
+        /// - `issuer`: The account who is able to mint into the collection.
+        /// - `max_supply`: The maximum number of commodities in the collection (minted - burned).
+        /// - `max_per_user`: The maximum number of commodities in the collection any single
+        ///   account may own.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(2), DispatchClass::Normal, Pays::No))]
+        pub fn create_collection(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			issuer: T::AccountId,
+			max_supply: u128,
+			max_per_user: u64,
+		) -> DispatchResultWithPostInfo {
+            T::CommodityAdmin::ensure_origin(origin)?;
+
+            let collection_id = Self::insert_collection(owner.clone(), issuer, max_supply, max_per_user)?;
+
+            Self::deposit_event(Event::CollectionCreated(collection_id, owner));
+            Ok(().into())
+        }
+
+        /// Change the issuer of a collection.
+        ///
+        /// The dispatch origin for this call must be the collection owner.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        pub fn set_collection_issuer(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+			issuer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            Collections::<T>::try_mutate(collection_id, |maybe_collection| -> Result<(), DispatchError> {
+                let collection = maybe_collection.as_mut().ok_or(Error::<T>::UnknownCollection)?;
+                ensure!(who == collection.owner, Error::<T>::NotCollectionOwner);
+                collection.issuer = issuer.clone();
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::CollectionIssuerChanged(collection_id, issuer));
+            Ok(().into())
+        }
+
+        /// Add or replace a meta of a collection.
+        ///
+        /// The dispatch origin for this call must be the collection owner. Funds of the owner are
+        /// reserved according to `MetadataDepositBase + MetadataDepositPerByte * bytes`, the
+        /// deposit of the replaced meta is returned.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        pub fn add_collection_meta(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+			key: Vec<u8>,
+			value: MetaValue<T::Hash>,
+		) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let collection = Self::collection(collection_id).ok_or(Error::<T>::UnknownCollection)?;
+            ensure!(who == collection.owner, Error::<T>::NotCollectionOwner);
+
+            CollectionMeta::<T>::try_mutate(collection_id, |metas| {
+                Self::put_meta(metas, &who, key, value, false)
+            })?;
+            Self::deposit_event(Event::CollectionMetadataEvent(collection_id, who));
+            Ok(().into())
+        }
+
+        /// Remove a meta of a collection and return its deposit.
+        ///
+        /// The dispatch origin for this call must be the collection owner.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        pub fn remove_collection_meta(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+			key: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let collection = Self::collection(collection_id).ok_or(Error::<T>::UnknownCollection)?;
+            ensure!(who == collection.owner, Error::<T>::NotCollectionOwner);
+
+            CollectionMeta::<T>::try_mutate_exists(collection_id, |maybe_metas| {
+                Self::take_meta(maybe_metas, &key)
+            })?;
+            Self::deposit_event(Event::CollectionMetadataRemoved(collection_id, key));
+            Ok(().into())
+        }
+
+        /// Create a new commodity in a collection from the provided commodity info and identify
+        /// the specified account as its owner. The ID of the new commodity will be equal to the
+        /// hash of the collection and the info that defines it, as calculated by the runtime
+        /// system's hashing algorithm.
+        ///
+        /// The dispatch origin for this call must be the commodity admin or the collection issuer.
+        ///
         /// This function will throw an error if it is called with commodity info that describes
-        /// an existing (duplicate) commodity, if the maximum number of this type of commodity already
-        /// exists or if the specified owner already owns the maximum number of this type of
-        /// commodity.
+        /// an existing (duplicate) commodity, if the maximum number of this type of commodity or of
+        /// the collection already exists or if the specified owner already owns the maximum number
+        /// of this type of commodity or of the collection.
         ///
+        /// - `collection_id`: The collection to mint into.
         /// - `owner_account`: Receiver of the commodity.
         /// - `commodity_info`: The information that defines the commodity.
-        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        #[pallet::weight((T::WeightInfo::mint(), DispatchClass::Normal, Pays::No))]
         pub fn mint(
 			origin: OriginFor<T>,
+			collection_id: CollectionId,
 			owner_account: T::AccountId,
 			commodity_info: T::CommodityInfo
 		) -> DispatchResultWithPostInfo {
-            T::CommodityAdmin::ensure_origin(origin)?;
+            // the commodity admin mints into any collection
+            if let Err(origin) = T::CommodityAdmin::try_origin(origin) {
+                let who = ensure_signed(origin)?;
+                let collection = Self::collection(collection_id).ok_or(Error::<T>::UnknownCollection)?;
+                ensure!(who == collection.issuer, Error::<T>::NotCollectionIssuer);
+            }
 
 			// mint asset
-            <Self as UniqueAssets<_>>::mint(&owner_account, commodity_info)?;
+            <Self as UniqueAssets<_>>::mint(collection_id, &owner_account, commodity_info)?;
 
             Ok(().into())
         }
@@ -131,7 +320,7 @@ pub mod pallet {
         ///
         /// - `commodity_id`: The hash (calculated by the runtime system's hashing algorithm)
         ///   of the info that defines the commodity to destroy.
-        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        #[pallet::weight((T::WeightInfo::burn(), DispatchClass::Normal, Pays::No))]
         pub fn burn(
 			origin: OriginFor<T>,
 			commodity_id: T::Hash,
@@ -154,7 +343,7 @@ pub mod pallet {
         /// - `dest_account`: Receiver of the commodity.
         /// - `commodity_id`: The hash (calculated by the runtime system's hashing algorithm)
         ///   of the info that defines the commodity to destroy.
-        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        #[pallet::weight((T::WeightInfo::transfer(), DispatchClass::Normal, Pays::No))]
         pub fn transfer(
 			origin: OriginFor<T>,
 			dest_account: T::AccountId,
@@ -168,33 +357,132 @@ pub mod pallet {
             Self::deposit_event(Event::Transferred(commodity_id.clone(), dest_account.clone()));
             Ok(().into())
         }
-        /// add meta for a specific nft
+        /// Add or replace a meta of a commodity.
+        ///
+        /// The dispatch origin for this call must be the commodity owner, or the collection issuer
+        /// for a locked meta. A locked meta can not be edited by the owner. Funds of the caller are
+        /// reserved according to `MetadataDepositBase + MetadataDepositPerByte * bytes`, the
+        /// deposit of the replaced meta is returned.
+        ///
+        /// - `key`: Limited in length by `KeyLimit`.
+        /// - `value`: The typed value, bytes are limited in length by `ValueLimit`.
+        /// - `locked`: Whether the meta is locked by the collection issuer.
         #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
         pub fn add_meta(
 			origin: OriginFor<T>,
 			commodity_id: T::Hash,
 			key: Vec<u8>,
-			value: bool
+			value: MetaValue<T::Hash>,
+			locked: bool,
 		) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
-            ensure!(who.clone() == AccountForCommodity::<T>::get(&commodity_id), Error::<T>::NotCommodityOwner);
-            let mut new_meta = Some(MetaKeyValue { key: key.clone(), value: value.clone() });
-
-            let mut metas = Self::meta_data(commodity_id).into_iter()
-            .filter_map(|l| if l.key == key { new_meta.take() } else { Some(l) })
-            .collect::<Vec<_>>();
+            Self::ensure_meta_editor(&who, &commodity_id, locked)?;
 
-            if let Some(meta) = new_meta {
-                metas.push(meta)
-            }
-            if metas.is_empty() {
-                NftMeta::<T>::remove(commodity_id);
-            } else {
-                NftMeta::<T>::insert(commodity_id, metas);
-            }
+            NftMeta::<T>::try_mutate(commodity_id, |metas| -> Result<(), DispatchError> {
+                if let Some(old) = metas.iter().find(|one| one.key == key) {
+                    Self::ensure_meta_editor(&who, &commodity_id, old.locked)?;
+                }
+                Self::put_meta(metas, &who, key, value, locked)
+            })?;
             Self::deposit_event(Event::MetadataEvent(commodity_id, who));
             Ok(().into())
         }
+
+        /// Remove a meta of a commodity and return its deposit.
+        ///
+        /// The dispatch origin for this call must be the commodity owner, or the collection issuer
+        /// for a locked meta.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        pub fn remove_meta(
+			origin: OriginFor<T>,
+			commodity_id: T::Hash,
+			key: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            NftMeta::<T>::try_mutate_exists(commodity_id, |maybe_metas| -> Result<(), DispatchError> {
+                let locked = maybe_metas.as_ref()
+                    .and_then(|metas| metas.iter().find(|one| one.key == key))
+                    .map(|one| one.locked)
+                    .ok_or(Error::<T>::UnknownMetaKey)?;
+                Self::ensure_meta_editor(&who, &commodity_id, locked)?;
+                Self::take_meta(maybe_metas, &key)
+            })?;
+            Self::deposit_event(Event::MetadataRemoved(commodity_id, key));
+            Ok(().into())
+        }
+
+        /// Remove all of the meta of a commodity that the caller is able to edit, and
+        /// return their deposits.
+        ///
+        /// The dispatch origin for this call must be the commodity owner, who removes the
+        /// unlocked meta, or the collection issuer, who removes the locked meta.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(1), DispatchClass::Normal, Pays::No))]
+        pub fn clear_meta(
+			origin: OriginFor<T>,
+			commodity_id: T::Hash,
+		) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let is_owner = Self::ensure_meta_editor(&who, &commodity_id, false).is_ok();
+            let is_issuer = Self::ensure_meta_editor(&who, &commodity_id, true).is_ok();
+            ensure!(is_owner || is_issuer, Error::<T>::NotCommodityOwner);
+
+            NftMeta::<T>::mutate_exists(commodity_id, |maybe_metas| {
+                if let Some(metas) = maybe_metas {
+                    metas.retain(|one| {
+                        let editable = if one.locked { is_issuer } else { is_owner };
+                        if editable {
+                            T::Currency::unreserve(&one.depositor, one.deposit);
+                        }
+                        !editable
+                    });
+                    if metas.is_empty() {
+                        *maybe_metas = None;
+                    }
+                }
+            });
+            Self::deposit_event(Event::MetadataCleared(commodity_id, who));
+            Ok(().into())
+        }
+
+        /// Extend the life of a commodity by burning some featured asset.
+        ///
+        /// The dispatch origin for this call must be the commodity owner. Every unit of the
+        /// burned asset extends `LifePerAsset` blocks.
+        ///
+        /// - `commodity_id`: The commodity to extend, it must not be decayed.
+        /// - `asset_id`: The featured asset to burn.
+        /// - `amount`: The amount of featured asset to burn.
+        #[pallet::weight((10_000 + T::DbWeight::get().writes(3), DispatchClass::Normal, Pays::No))]
+        pub fn extend_life(
+			origin: OriginFor<T>,
+			commodity_id: T::Hash,
+			asset_id: AssetIdOf<T>,
+			amount: AssetBalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(who == AccountForCommodity::<T>::get(&commodity_id), Error::<T>::NotCommodityOwner);
+            ensure!(T::FeaturedAssets::is_in_using(asset_id), Error::<T>::AssetNotUsed);
+
+            NftExistInfo::<T>::try_mutate(commodity_id, |info| -> Result<(), DispatchError> {
+                ensure!(!info.is_decayed, Error::<T>::CommodityDecayed);
+                ensure!(!info.decayed_at.is_zero(), Error::<T>::CommodityNeverDecays);
+
+                let units: u32 = amount.unique_saturated_into();
+                let extension = T::LifePerAsset::get().saturating_mul(units.into());
+                ensure!(!extension.is_zero(), Error::<T>::ZeroExtension);
+
+                T::FeaturedAssets::burn(asset_id, &who, amount)?;
+
+                Self::remove_decay(info.decayed_at, &commodity_id);
+                info.decayed_at = info.decayed_at.saturating_add(extension);
+                Self::insert_decay(info.decayed_at, commodity_id);
+
+                Self::deposit_event(Event::LifeExtended(commodity_id, info.decayed_at));
+                Ok(())
+            })?;
+            Ok(().into())
+        }
 	}
 
 	#[pallet::storage]
@@ -219,16 +507,105 @@ pub mod pallet {
 	>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn commodities_for_account)]
-	/// A mapping from an account to a list of all of the commodities of this type that are owned by it.
-	pub type CommoditiesForAccount<T: Config> = StorageMap<
+	/// The commodities of this type that are owned by an account.
+	pub type OwnedCommodities<T: Config> = StorageDoubleMap<
 		_,
 		Blake2_128Concat,
 		T::AccountId,
-		Vec<Commodity<T>>,
+		Identity,
+		T::Hash,
+		()
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn commodity_info)]
+	/// A mapping from a commodity ID to the info that defines it.
+	pub type CommodityInfos<T: Config> = StorageMap<
+		_,
+		Identity,
+		T::Hash,
+		T::CommodityInfo
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_collection_id)]
+	/// The ID of the next created collection.
+	pub type NextCollectionId<T> = StorageValue<_, CollectionId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn collection)]
+	/// A mapping from a collection ID to its owner, issuer, limits and supply.
+	pub type Collections<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		CollectionId,
+		CollectionInfo<T::AccountId>
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn collection_meta)]
+	/// meta data for a collection
+	pub type CollectionMeta<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		CollectionId,
+		Vec<MetaKeyValueOf<T>>,
 		ValueQuery
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn collection_for_commodity)]
+	/// A mapping from a commodity ID to the collection it belongs to.
+	pub type CollectionForCommodity<T: Config> = StorageMap<
+		_,
+		Identity,
+		T::Hash,
+		CollectionId
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn collection_total_for_account)]
+	/// The total number of commodities in a collection owned by an account.
+	pub type CollectionTotalForAccount<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		CollectionId,
+		Blake2_128Concat,
+		T::AccountId,
+		u64,
+		ValueQuery
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	/// The storage layout version, used to run migrations.
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// The collections created in order of ID: owner, issuer, max supply and max per user.
+		pub collections: Vec<(T::AccountId, T::AccountId, u128, u64)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { collections: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (owner, issuer, max_supply, max_per_user) in self.collections.iter() {
+				Pallet::<T>::insert_collection(owner.clone(), issuer.clone(), *max_supply, *max_per_user)
+					.expect("the collection IDs of genesis are available; qed");
+			}
+			// a new chain starts with the latest storage layout
+			StorageVersion::<T>::put(Releases::V6);
+		}
+	}
+
 	#[pallet::storage]
 	#[pallet::getter(fn account_for_commodity)]
 	/// A mapping from a commodity ID to the account that owns it.
@@ -247,7 +624,7 @@ pub mod pallet {
 		_,
 		Identity,
 		T::Hash,
-		Vec<MetaKeyValue>,
+		Vec<MetaKeyValueOf<T>>,
 		ValueQuery
 	>;
 
@@ -262,6 +639,16 @@ pub mod pallet {
 		ValueQuery
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn decay_queue)]
+	/// the commodities which will decay at the block
+	pub type DecayQueue<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Vec<T::Hash>
+	>;
+
 	#[pallet::event]
 	#[pallet::metadata(T::AccountId = "AccountId", T::Hash = "Hash")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -274,6 +661,22 @@ pub mod pallet {
         Transferred(T::Hash, T::AccountId),
         /// change metadata event
         MetadataEvent(T::Hash, T::AccountId),
+        /// The commodity has decayed, it was burned or flagged. \[commodity_id, burned\]
+        Decayed(T::Hash, bool),
+        /// The life of commodity has been extended. \[commodity_id, decayed_at\]
+        LifeExtended(T::Hash, T::BlockNumber),
+        /// A collection has been created. \[collection_id, owner\]
+        CollectionCreated(CollectionId, T::AccountId),
+        /// The issuer of a collection has been changed. \[collection_id, issuer\]
+        CollectionIssuerChanged(CollectionId, T::AccountId),
+        /// change collection metadata event \[collection_id, owner\]
+        CollectionMetadataEvent(CollectionId, T::AccountId),
+        /// A meta of the collection has been removed. \[collection_id, key\]
+        CollectionMetadataRemoved(CollectionId, Vec<u8>),
+        /// A meta of the commodity has been removed. \[commodity_id, key\]
+        MetadataRemoved(T::Hash, Vec<u8>),
+        /// The meta of the commodity editable by the account has been removed. \[commodity_id, who\]
+        MetadataCleared(T::Hash, T::AccountId),
 	}
 
 	#[pallet::error]
@@ -290,29 +693,264 @@ pub mod pallet {
         // Thrown when an attempt is made to mint or transfer a commodity to an account that already
         // owns the maximum number of this type of commodity.
         TooManyCommoditiesForAccount,
+        // Thrown when there is an attempt to burn or transfer a locked commodity.
+        CommodityLocked,
+        // Thrown when there is an attempt to extend a decayed commodity.
+        CommodityDecayed,
+        // Thrown when there is an attempt to extend a commodity without life time.
+        CommodityNeverDecays,
+        // Thrown when the featured asset to burn is not in use.
+        AssetNotUsed,
+        // Thrown when the burned featured asset extends no block.
+        ZeroExtension,
+        // Thrown when there is an attempt to use a nonexistent collection.
+        UnknownCollection,
+        // Thrown when someone who is not the owner of a collection attempts to manage it.
+        NotCollectionOwner,
+        // Thrown when someone who is not the issuer of a collection attempts to mint into it.
+        NotCollectionIssuer,
+        // Thrown when there is an attempt to mint a commodity and the maximum number of the
+        // collection already exists.
+        TooManyCommoditiesInCollection,
+        // Thrown when an attempt is made to mint or transfer a commodity to an account that already
+        // owns the maximum number of the collection.
+        TooManyCollectionCommoditiesForAccount,
+        // Thrown when no more collection can be created.
+        NoAvailableCollectionId,
+        // Thrown when a meta key or bytes value is too long.
+        BadMetadata,
+        // Thrown when there is an attempt to add a meta to a commodity or a collection which
+        // already has the maximum number of meta.
+        TooManyMeta,
+        // Thrown when there is an attempt to remove a nonexistent meta.
+        UnknownMetaKey,
+        // Thrown when someone who is not the collection issuer attempts to edit a locked meta.
+        MetaLocked,
 	}
 }
 
-#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, Ord, PartialOrd)]
-pub struct MetaKeyValue {
-    key: Vec<u8>,
-    value: bool,
+/// The typed value of a meta
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum MetaValue<Hash> {
+    Bytes(Vec<u8>),
+    U32(u32),
+    U128(u128),
+    Bool(bool),
+    Hash(Hash),
+}
+
+/// A meta of a commodity or a collection
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct MetaKeyValue<Hash, AccountId, Balance> {
+    pub key: Vec<u8>,
+    pub value: MetaValue<Hash>,
+    /// locked by the collection issuer, the owner can not edit it
+    pub locked: bool,
+    /// the account who reserved the deposit
+    pub depositor: AccountId,
+    pub deposit: Balance,
+}
+
+pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+pub type MetaKeyValueOf<T> = MetaKeyValue<<T as frame_system::Config>::Hash, <T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+/// What happens to a commodity when it decays
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum DecayPolicy {
+    /// The commodity is burned, or flagged if it is locked
+    Burn,
+    /// The commodity is flagged as decayed and can not be used any more
+    Flag,
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default)]
 pub struct ExistInfo<BlockNumber> {
     generated_at: BlockNumber,
+    /// zero if the commodity never decays
     decayed_at: BlockNumber,
+    is_decayed: bool,
 }
 
+pub type AssetIdOf<T> = <<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::AssetId;
+pub type AssetBalanceOf<T> = <<T as Config>::FeaturedAssets as FeaturedAssets<<T as frame_system::Config>::AccountId>>::Balance;
+
 /// Associates a commodity with its ID.
 pub type Commodity<T> = (<T as frame_system::Config>::Hash, <T as Config>::CommodityInfo);
 
+/// The type used to identify collections of commodities.
+pub type CollectionId = u32;
+
+/// A collection of commodities sharing the same rules.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default)]
+pub struct CollectionInfo<AccountId> {
+    /// The account who manages the collection
+    pub owner: AccountId,
+    /// The account who is able to mint into the collection
+    pub issuer: AccountId,
+    /// The maximum number of commodities in the collection (minted - burned)
+    pub max_supply: u128,
+    /// The maximum number of commodities in the collection any single account may own
+    pub max_per_user: u64,
+    /// The number of commodities in the collection (minted - burned)
+    pub supply: u128,
+}
+
+/// The storage layout versions of this pallet.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum Releases {
+    /// A sorted `Vec<Commodity>` per account, the info is `OldCommodityInfo`.
+    V1,
+    /// The info is `CommodityInfo`.
+    V2,
+    /// A double map from account and commodity ID, the info is stored once per ID.
+    V3,
+    /// Every commodity belongs to a collection.
+    V4,
+    /// The meta is typed, bounded and has a deposit.
+    V5,
+    /// The exist info has a decay flag, the decaying commodities are in `DecayQueue`.
+    V6,
+}
+
+impl Default for Releases {
+    fn default() -> Self { Self::V1 }
+}
+
+impl<T: Config> Pallet<T> {
+    /// All of the commodities of this type that are owned by an account, sorted by ID.
+    pub fn commodities_for_account<KArg: EncodeLike<T::AccountId>>(account: KArg) -> Vec<Commodity<T>> {
+        OwnedCommodities::<T>::iter_prefix(account)
+            .filter_map(|(commodity_id, _)| {
+                CommodityInfos::<T>::get(&commodity_id).map(|info| (commodity_id, info))
+            })
+            .collect()
+    }
+
+    /// create a collection with the next collection ID
+    fn insert_collection(
+        owner: T::AccountId,
+        issuer: T::AccountId,
+        max_supply: u128,
+        max_per_user: u64,
+    ) -> Result<CollectionId, DispatchError> {
+        NextCollectionId::<T>::try_mutate(|next_id| -> Result<CollectionId, DispatchError> {
+            let collection_id = *next_id;
+            *next_id = next_id.checked_add(1).ok_or(Error::<T>::NoAvailableCollectionId)?;
+            Collections::<T>::insert(collection_id, CollectionInfo {
+                owner,
+                issuer,
+                max_supply,
+                max_per_user,
+                supply: 0,
+            });
+            Ok(collection_id)
+        })
+    }
+
+    /// ensure the account is able to edit a meta of a commodity, the owner edits the
+    /// unlocked meta and the collection issuer edits the locked meta
+    fn ensure_meta_editor(who: &T::AccountId, commodity_id: &T::Hash, locked: bool) -> DispatchResult {
+        if locked {
+            let issuer = Self::collection_for_commodity(commodity_id)
+                .and_then(|collection_id| Self::collection(collection_id))
+                .map(|collection| collection.issuer);
+            ensure!(issuer.as_ref() == Some(who), Error::<T>::MetaLocked);
+        } else {
+            ensure!(*who == Self::account_for_commodity(commodity_id), Error::<T>::NotCommodityOwner);
+        }
+        Ok(())
+    }
+
+    /// add or replace the meta with the same key, the deposit is reserved from `who`
+    fn put_meta(
+        metas: &mut Vec<MetaKeyValueOf<T>>,
+        who: &T::AccountId,
+        key: Vec<u8>,
+        value: MetaValue<T::Hash>,
+        locked: bool,
+    ) -> DispatchResult {
+        ensure!(key.len() <= T::KeyLimit::get() as usize, Error::<T>::BadMetadata);
+        if let MetaValue::Bytes(bytes) = &value {
+            ensure!(bytes.len() <= T::ValueLimit::get() as usize, Error::<T>::BadMetadata);
+        }
+        let maybe_pos = metas.iter().position(|one| one.key == key);
+        if maybe_pos.is_none() {
+            ensure!(metas.len() < T::MetadataLimit::get() as usize, Error::<T>::TooManyMeta);
+        }
+
+        let bytes_used = key.len() + value.encode().len();
+        let deposit = T::MetadataDepositPerByte::get()
+            .saturating_mul((bytes_used as u32).into())
+            .saturating_add(T::MetadataDepositBase::get());
+        T::Currency::reserve(who, deposit)?;
+
+        let meta = MetaKeyValue { key, value, locked, depositor: who.clone(), deposit };
+        match maybe_pos {
+            Some(pos) => {
+                let old = sp_std::mem::replace(&mut metas[pos], meta);
+                T::Currency::unreserve(&old.depositor, old.deposit);
+            },
+            None => metas.push(meta),
+        }
+        Ok(())
+    }
+
+    /// remove the meta with the key and return its deposit
+    fn take_meta(maybe_metas: &mut Option<Vec<MetaKeyValueOf<T>>>, key: &[u8]) -> DispatchResult {
+        let metas = maybe_metas.as_mut().ok_or(Error::<T>::UnknownMetaKey)?;
+        let pos = metas.iter().position(|one| one.key == key).ok_or(Error::<T>::UnknownMetaKey)?;
+        let old = metas.remove(pos);
+        T::Currency::unreserve(&old.depositor, old.deposit);
+        if metas.is_empty() {
+            *maybe_metas = None;
+        }
+        Ok(())
+    }
+
+    /// add a commodity to the decay queue
+    fn insert_decay(decayed_at: T::BlockNumber, commodity_id: T::Hash) {
+        DecayQueue::<T>::mutate(decayed_at, |maybe_list| {
+            maybe_list.get_or_insert_with(Vec::new).push(commodity_id);
+        });
+    }
+
+    /// remove a commodity from the decay queue
+    fn remove_decay(decayed_at: T::BlockNumber, commodity_id: &T::Hash) {
+        DecayQueue::<T>::mutate_exists(decayed_at, |maybe_list| {
+            if let Some(list) = maybe_list {
+                list.retain(|one| one != commodity_id);
+                if list.is_empty() {
+                    *maybe_list = None;
+                }
+            }
+        });
+    }
+
+    /// burn or flag a commodity according to `DecayPolicy`
+    fn try_decay(commodity_id: &T::Hash, now: T::BlockNumber) {
+        let info = Self::exist_info(commodity_id);
+        // burned, or delayed from a full block
+        if info.is_decayed || info.decayed_at.is_zero() || info.decayed_at > now {
+            return;
+        }
+
+        let burned = T::DecayPolicy::get() == DecayPolicy::Burn &&
+            <Self as UniqueAssets<_>>::burn(commodity_id).is_ok();
+        if burned {
+            Self::deposit_event(Event::Burned(*commodity_id));
+        } else {
+            NftExistInfo::<T>::mutate(commodity_id, |info| info.is_decayed = true);
+        }
+        Self::deposit_event(Event::Decayed(*commodity_id, burned));
+    }
+}
+
 impl<T: Config> UniqueAssets<T::AccountId> for Pallet<T> {
     type AssetId = T::Hash;
     type AssetInfo = T::CommodityInfo;
     type AssetLimit = T::CommodityLimit;
     type UserAssetLimit = T::UserCommodityLimit;
+    type CollectionId = CollectionId;
 
     fn total() -> u128 {
         Self::total()
@@ -334,11 +972,39 @@ impl<T: Config> UniqueAssets<T::AccountId> for Pallet<T> {
         Self::account_for_commodity(commodity_id)
     }
 
+    fn is_decayed(commodity_id: &T::Hash) -> bool {
+        Self::exist_info(commodity_id).is_decayed
+    }
+
+    fn collection_of(commodity_id: &T::Hash) -> Option<CollectionId> {
+        Self::collection_for_commodity(commodity_id)
+    }
+
+    fn total_in_collection(collection_id: CollectionId) -> u128 {
+        Self::collection(collection_id).map(|collection| collection.supply).unwrap_or_default()
+    }
+
+    fn total_for_account_in_collection(collection_id: CollectionId, account: &T::AccountId) -> u64 {
+        Self::collection_total_for_account(collection_id, account)
+    }
+
+    fn assets_for_account_in_collection(
+        collection_id: CollectionId,
+        account: &T::AccountId,
+    ) -> Vec<Commodity<T>> {
+        Self::commodities_for_account(account)
+            .into_iter()
+            .filter(|(commodity_id, _)| Self::collection_for_commodity(commodity_id) == Some(collection_id))
+            .collect()
+    }
+
     fn mint(
+        collection_id: CollectionId,
         owner_account: &T::AccountId,
         commodity_info: T::CommodityInfo,
     ) -> Result<T::Hash, DispatchError> {
-        let commodity_id = T::Hashing::hash_of(&commodity_info);
+        let mut collection = Self::collection(collection_id).ok_or(Error::<T>::UnknownCollection)?;
+        let commodity_id = T::Hashing::hash_of(&(collection_id, &commodity_info));
 
         ensure!(
             !AccountForCommodity::<T>::contains_key(&commodity_id),
@@ -355,24 +1021,43 @@ impl<T: Config> UniqueAssets<T::AccountId> for Pallet<T> {
             Error::<T>::TooManyCommodities
         );
 
-        let new_commodity = (commodity_id, commodity_info);
+        ensure!(
+            Self::collection_total_for_account(collection_id, owner_account) < collection.max_per_user,
+            Error::<T>::TooManyCollectionCommoditiesForAccount
+        );
+
+        ensure!(
+            collection.supply < collection.max_supply,
+            Error::<T>::TooManyCommoditiesInCollection
+        );
 
         Total::<T>::mutate(|total| *total += 1);
         TotalForAccount::<T>::mutate(owner_account, |total| *total += 1);
-        CommoditiesForAccount::<T>::mutate(owner_account, |commodities| {
-            match commodities.binary_search(&new_commodity) {
-                Ok(_pos) => {} // should never happen
-                Err(pos) => commodities.insert(pos, new_commodity),
-            }
-        });
+        OwnedCommodities::<T>::insert(owner_account, commodity_id, ());
+        CommodityInfos::<T>::insert(commodity_id, commodity_info);
         AccountForCommodity::<T>::insert(commodity_id, &owner_account);
 
-		// add exist info
+        collection.supply += 1;
+        Collections::<T>::insert(collection_id, collection);
+        CollectionTotalForAccount::<T>::mutate(collection_id, owner_account, |total| *total += 1);
+        CollectionForCommodity::<T>::insert(commodity_id, collection_id);
+
+		// add exist info, the commodity never decays without life time
 		let current_block = frame_system::Module::<T>::block_number();
+		let life_time = T::LifeTime::base_age(0);
+		let decayed_at = if life_time.is_zero() {
+			Zero::zero()
+		} else {
+			current_block.saturating_add(life_time)
+		};
 		NftExistInfo::<T>::insert(commodity_id, ExistInfo {
 			generated_at: current_block,
-			decayed_at: current_block + T::LifeTime::base_age(0),
+			decayed_at,
+			is_decayed: false,
 		});
+		if !decayed_at.is_zero() {
+			Self::insert_decay(decayed_at, commodity_id);
+		}
 
 		// deposit event
 		Self::deposit_event(Event::Minted(commodity_id.clone(), owner_account.clone()));
@@ -383,24 +1068,32 @@ impl<T: Config> UniqueAssets<T::AccountId> for Pallet<T> {
     fn burn(commodity_id: &T::Hash) -> DispatchResult {
         let owner = Self::owner_of(commodity_id);
         ensure!(
-            owner != T::AccountId::default(),
+            owner != T::AccountId::default() && OwnedCommodities::<T>::contains_key(&owner, commodity_id),
             Error::<T>::NonexistentCommodity
         );
+        ensure!(
+            !T::AssetLock::is_locked(&owner, commodity_id),
+            Error::<T>::CommodityLocked
+        );
 
-        let burn_commodity = (*commodity_id, T::CommodityInfo::default());
-
-        Total::<T>::mutate(|total| *total -= 1);
+        Total::<T>::mutate(|total| *total = total.saturating_sub(1));
         Burned::<T>::mutate(|total| *total += 1);
-        TotalForAccount::<T>::mutate(&owner, |total| *total -= 1);
-        CommoditiesForAccount::<T>::mutate(owner, |commodities| {
-            let pos = commodities
-                .binary_search(&burn_commodity)
-                .expect("We already checked that we have the correct owner;");
-            commodities.remove(pos);
-        });
+        TotalForAccount::<T>::mutate(&owner, |total| *total = total.saturating_sub(1));
+        OwnedCommodities::<T>::remove(&owner, commodity_id);
+        CommodityInfos::<T>::remove(commodity_id);
         AccountForCommodity::<T>::remove(&commodity_id);
-        // remove meta and exist info
-        NftMeta::<T>::remove(&commodity_id);
+        if let Some(collection_id) = CollectionForCommodity::<T>::take(commodity_id) {
+            Collections::<T>::mutate(collection_id, |maybe_collection| {
+                if let Some(collection) = maybe_collection {
+                    collection.supply = collection.supply.saturating_sub(1);
+                }
+            });
+            CollectionTotalForAccount::<T>::mutate(collection_id, &owner, |total| *total = total.saturating_sub(1));
+        }
+        // remove meta and exist info, the deposits of meta are returned
+        for meta in NftMeta::<T>::take(&commodity_id) {
+            T::Currency::unreserve(&meta.depositor, meta.deposit);
+        }
         NftExistInfo::<T>::remove(&commodity_id);
 
         Ok(())
@@ -412,31 +1105,36 @@ impl<T: Config> UniqueAssets<T::AccountId> for Pallet<T> {
     ) -> DispatchResult {
         let owner = Self::owner_of(&commodity_id);
         ensure!(
-            owner != T::AccountId::default(),
+            owner != T::AccountId::default() && OwnedCommodities::<T>::contains_key(&owner, commodity_id),
             Error::<T>::NonexistentCommodity
         );
+        ensure!(
+            !T::AssetLock::is_locked(&owner, commodity_id),
+            Error::<T>::CommodityLocked
+        );
 
         ensure!(
             Self::total_for_account(dest_account) < Self::UserAssetLimit::get(),
             Error::<T>::TooManyCommoditiesForAccount
         );
 
-        let xfer_commodity = (*commodity_id, <T>::CommodityInfo::default());
+        let maybe_collection_id = Self::collection_for_commodity(commodity_id);
+        if let Some(collection_id) = maybe_collection_id {
+            let max_per_user = Self::collection(collection_id)
+                .map(|collection| collection.max_per_user)
+                .unwrap_or_default();
+            ensure!(
+                Self::collection_total_for_account(collection_id, dest_account) < max_per_user,
+                Error::<T>::TooManyCollectionCommoditiesForAccount
+            );
+            CollectionTotalForAccount::<T>::mutate(collection_id, &owner, |total| *total = total.saturating_sub(1));
+            CollectionTotalForAccount::<T>::mutate(collection_id, dest_account, |total| *total += 1);
+        }
 
-        TotalForAccount::<T>::mutate(&owner, |total| *total -= 1);
+        TotalForAccount::<T>::mutate(&owner, |total| *total = total.saturating_sub(1));
         TotalForAccount::<T>::mutate(dest_account, |total| *total += 1);
-        let commodity = CommoditiesForAccount::<T>::mutate(owner, |commodities| {
-            let pos = commodities
-                .binary_search(&xfer_commodity)
-                .expect("We already checked that we have the correct owner; qed");
-            commodities.remove(pos)
-        });
-        CommoditiesForAccount::<T>::mutate(dest_account, |commodities| {
-            match commodities.binary_search(&commodity) {
-                Ok(_pos) => {} // should never happen
-                Err(pos) => commodities.insert(pos, commodity),
-            }
-        });
+        OwnedCommodities::<T>::remove(&owner, commodity_id);
+        OwnedCommodities::<T>::insert(dest_account, commodity_id, ());
         AccountForCommodity::<T>::insert(&commodity_id, &dest_account);
 
         Ok(())
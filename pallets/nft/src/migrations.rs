@@ -0,0 +1,180 @@
+//! Storage migrations of the commodities pallet.
+
+use super::*;
+use frame_support::{
+	Blake2_128Concat, StorageHasher,
+	storage::migration::{storage_key_iter, put_storage_value},
+	traits::PalletInfo,
+};
+
+/// Translate the info of every commodity in `CommoditiesForAccount` from `OldCommodityInfo`
+/// into `CommodityInfo`, before any other migration reads it.
+pub fn migrate_to_category<T: Config>() -> Weight {
+	let pallet_name = <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>()
+		.expect("the commodities pallet is part of the runtime; qed");
+
+	let translated: Vec<(T::AccountId, Vec<Commodity<T>>)> = storage_key_iter::<
+		T::AccountId,
+		Vec<(T::Hash, T::OldCommodityInfo)>,
+		Blake2_128Concat,
+	>(pallet_name.as_bytes(), b"CommoditiesForAccount")
+		.map(|(account, commodities)| {
+			let commodities = commodities.into_iter()
+				.map(|(commodity_id, old)| (commodity_id, old.into()))
+				.collect();
+			(account, commodities)
+		})
+		.collect();
+
+	let count = translated.len() as Weight;
+	for (account, commodities) in translated {
+		put_storage_value(
+			pallet_name.as_bytes(),
+			b"CommoditiesForAccount",
+			&Blake2_128Concat::hash(&account.encode()),
+			commodities,
+		);
+	}
+
+	T::DbWeight::get().reads_writes(count, count)
+}
+
+/// Move the sorted `Vec<Commodity>` of every account in `CommoditiesForAccount`
+/// into `OwnedCommodities` and `CommodityInfos`, the old entries are removed.
+///
+/// The info must be translated by `migrate_to_category` before, or the entries in the old
+/// layout could not be decoded.
+pub fn migrate_to_double_map<T: Config>() -> Weight {
+	let pallet_name = <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>()
+		.expect("the commodities pallet is part of the runtime; qed");
+
+	let mut reads: Weight = 0;
+	let mut writes: Weight = 0;
+	for (account, commodities) in storage_key_iter::<T::AccountId, Vec<Commodity<T>>, Blake2_128Concat>(
+		pallet_name.as_bytes(),
+		b"CommoditiesForAccount",
+	).drain() {
+		// the old entry is read and removed
+		reads = reads.saturating_add(1);
+		writes = writes.saturating_add(1);
+		for (commodity_id, commodity_info) in commodities {
+			OwnedCommodities::<T>::insert(&account, commodity_id, ());
+			CommodityInfos::<T>::insert(commodity_id, commodity_info);
+			writes = writes.saturating_add(2);
+		}
+	}
+
+	T::DbWeight::get().reads_writes(reads, writes)
+}
+
+/// Put all of the existing commodities without a collection into a new legacy collection,
+/// owned and issued by `LegacyCollectionOwner` and limited by the limits of this type of commodity.
+///
+/// The legacy collection is also created if there is no collection at all, so the first
+/// collection ID, which is used for equipments by the runtime, always exists.
+pub fn migrate_to_collections<T: Config>() -> Weight {
+	let collection_id = NextCollectionId::<T>::get();
+	let mut reads: Weight = 2;
+	let mut writes: Weight = 0;
+	let legacy_owner = T::LegacyCollectionOwner::get();
+	let mut collection = CollectionInfo {
+		owner: legacy_owner.clone(),
+		issuer: legacy_owner,
+		max_supply: T::CommodityLimit::get(),
+		max_per_user: T::UserCommodityLimit::get(),
+		supply: 0,
+	};
+	for (commodity_id, owner) in AccountForCommodity::<T>::iter() {
+		// the commodity already in a collection is counted by it
+		reads = reads.saturating_add(2);
+		if CollectionForCommodity::<T>::contains_key(commodity_id) {
+			continue;
+		}
+		CollectionForCommodity::<T>::insert(commodity_id, collection_id);
+		CollectionTotalForAccount::<T>::mutate(collection_id, &owner, |total| *total += 1);
+		collection.supply = collection.supply.saturating_add(1);
+		reads = reads.saturating_add(1);
+		writes = writes.saturating_add(2);
+	}
+	if collection.supply == 0 && collection_id != 0 {
+		return T::DbWeight::get().reads_writes(reads, writes);
+	}
+	Collections::<T>::insert(collection_id, collection);
+	NextCollectionId::<T>::put(collection_id.saturating_add(1));
+
+	T::DbWeight::get().reads_writes(reads.saturating_add(1), writes.saturating_add(2))
+}
+
+/// The untyped meta before `Releases::V5`.
+#[derive(Decode)]
+struct OldMetaKeyValue {
+	key: Vec<u8>,
+	value: bool,
+}
+
+/// Turn the untyped meta into unlocked `MetaValue::Bool` meta without deposit, deposited
+/// by the commodity owner or the collection owner.
+pub fn migrate_to_typed_meta<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+	let into_typed = |metas: Vec<OldMetaKeyValue>, depositor: T::AccountId| -> Vec<MetaKeyValueOf<T>> {
+		metas.into_iter().map(|old| MetaKeyValue {
+			key: old.key,
+			value: MetaValue::Bool(old.value),
+			locked: false,
+			depositor: depositor.clone(),
+			deposit: Zero::zero(),
+		}).collect()
+	};
+
+	NftMeta::<T>::translate::<Vec<OldMetaKeyValue>, _>(|commodity_id, metas| {
+		translated = translated.saturating_add(1);
+		Some(into_typed(metas, AccountForCommodity::<T>::get(commodity_id)))
+	});
+	CollectionMeta::<T>::translate::<Vec<OldMetaKeyValue>, _>(|collection_id, metas| {
+		translated = translated.saturating_add(1);
+		let owner = Collections::<T>::get(collection_id)
+			.map(|collection| collection.owner)
+			.unwrap_or_default();
+		Some(into_typed(metas, owner))
+	});
+
+	T::DbWeight::get().reads_writes(translated.saturating_mul(2), translated)
+}
+
+/// The exist info before `Releases::V6`.
+#[derive(Decode)]
+struct OldExistInfo<BlockNumber> {
+	generated_at: BlockNumber,
+	decayed_at: BlockNumber,
+}
+
+/// Add the decay flag to the exist info of every commodity and put the decaying ones into
+/// `DecayQueue`, the overdue ones decay in the next block. A commodity which decayed at the
+/// block of generation had no life time and never decays.
+pub fn migrate_to_decay_queue<T: Config>() -> Weight {
+	let next_block = frame_system::Module::<T>::block_number().saturating_add(One::one());
+	let mut translated: Weight = 0;
+	let mut queued: Vec<(T::BlockNumber, T::Hash)> = Vec::new();
+	NftExistInfo::<T>::translate::<OldExistInfo<T::BlockNumber>, _>(|commodity_id, old| {
+		translated = translated.saturating_add(1);
+		let decayed_at = if old.decayed_at == old.generated_at {
+			Zero::zero()
+		} else {
+			old.decayed_at
+		};
+		if !decayed_at.is_zero() {
+			queued.push((decayed_at.max(next_block), commodity_id));
+		}
+		Some(ExistInfo {
+			generated_at: old.generated_at,
+			decayed_at,
+			is_decayed: false,
+		})
+	});
+	let queued_count = queued.len() as Weight;
+	for (decay_at, commodity_id) in queued {
+		Pallet::<T>::insert_decay(decay_at, commodity_id);
+	}
+
+	T::DbWeight::get().reads_writes(translated.saturating_add(queued_count), translated.saturating_add(queued_count))
+}
@@ -1,7 +1,13 @@
 use crate as mc_nft;
-use crate::Module;
-use frame_support::parameter_types;
+use crate::{Module, DecayPolicy};
+use std::cell::RefCell;
+use frame_support::{parameter_types, dispatch::DispatchResultWithPostInfo, traits::{Get, GenesisBuild}};
+use mc_support::{
+  primitives::AssetFeature,
+  traits::{UniqueAssetLock, LifeTime, FeaturedAssets},
+};
 use frame_system as system;
+use codec::{Encode, Decode};
 use sp_core::H256;
 use sp_runtime::{
   testing::Header,
@@ -19,7 +25,8 @@ frame_support::construct_runtime!(
     UncheckedExtrinsic = UncheckedExtrinsic,
   {
     System: frame_system::{Module, Call, Config, Storage, Event<T>},
-    TemplateModule: mc_nft::{Module, Call, Storage, Event<T>},
+    Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+    TemplateModule: mc_nft::{Module, Call, Storage, Config<T>, Event<T>},
   }
 );
 
@@ -46,34 +53,170 @@ impl system::Config for Test {
   type BlockHashCount = BlockHashCount;
   type Version = ();
   type PalletInfo = PalletInfo;
-  type AccountData = ();
+  type AccountData = pallet_balances::AccountData<u64>;
   type OnNewAccount = ();
   type OnKilledAccount = ();
   type SystemWeightInfo = ();
   type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+  pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+  type MaxLocks = ();
+  type Balance = u64;
+  type Event = Event;
+  type DustRemoval = ();
+  type ExistentialDeposit = ExistentialDeposit;
+  type AccountStore = System;
+  type WeightInfo = ();
+}
+
 parameter_types! {
   pub const MaxCommodities: u128 = 5;
   pub const MaxCommoditiesPerUser: u64 = 2;
   pub const DecayTime: u64 = 100;
 }
 
+thread_local! {
+  static LOCKED: RefCell<Vec<H256>> = RefCell::new(Vec::new());
+}
+
+/// Lock the commodities in `LOCKED` whoever owns them.
+pub struct TestLock;
+impl UniqueAssetLock<u64, H256> for TestLock {
+  fn is_locked(_: &u64, commodity_id: &H256) -> bool {
+    LOCKED.with(|v| v.borrow().contains(commodity_id))
+  }
+}
+
+pub fn set_locked(commodity_id: H256, locked: bool) {
+  LOCKED.with(|v| {
+    let mut list = v.borrow_mut();
+    list.retain(|one| *one != commodity_id);
+    if locked {
+      list.push(commodity_id);
+    }
+  });
+}
+
+/// Commodities decay after `DecayTime` blocks.
+pub struct TestLifeTime;
+impl LifeTime<u64> for TestLifeTime {
+  fn base_age(_: u32) -> u64 { DecayTime::get() }
+}
+
+thread_local! {
+  static ASSETS: RefCell<Vec<(u64, u64)>> = RefCell::new(Vec::new());
+  static DECAY_POLICY: RefCell<DecayPolicy> = RefCell::new(DecayPolicy::Burn);
+}
+
+/// A single featured asset 0, stored as the balances of accounts.
+pub struct TestAssets;
+impl FeaturedAssets<u64> for TestAssets {
+  type AssetId = u32;
+  type Amount = u64;
+  type Balance = u64;
+
+  fn is_in_using(id: u32) -> bool { id == 0 }
+  fn total_supply(_: u32) -> u64 {
+    ASSETS.with(|v| v.borrow().iter().map(|(_, balance)| balance).sum())
+  }
+  fn balance(_: u32, who: u64) -> u64 {
+    ASSETS.with(|v| v.borrow().iter().find(|(one, _)| *one == who).map(|(_, balance)| *balance).unwrap_or(0))
+  }
+  fn feature(_: u32) -> Option<AssetFeature> { None }
+  fn mint(_: u32, beneficiary: &u64, amount: u64) -> DispatchResultWithPostInfo {
+    let balance = Self::balance(0, *beneficiary);
+    ASSETS.with(|v| {
+      let mut list = v.borrow_mut();
+      list.retain(|(one, _)| one != beneficiary);
+      list.push((*beneficiary, balance + amount));
+    });
+    Ok(().into())
+  }
+  fn burn(_: u32, who: &u64, amount: u64) -> DispatchResultWithPostInfo {
+    let balance = Self::balance(0, *who);
+    frame_support::ensure!(balance >= amount, "balance too low");
+    ASSETS.with(|v| {
+      let mut list = v.borrow_mut();
+      list.retain(|(one, _)| one != who);
+      list.push((*who, balance - amount));
+    });
+    Ok(().into())
+  }
+  fn transfer(_: u32, origin: &u64, dest: &u64, amount: u64) -> DispatchResultWithPostInfo {
+    Self::burn(0, origin, amount)?;
+    Self::mint(0, dest, amount)
+  }
+}
+
+pub struct TestDecayPolicy;
+impl Get<DecayPolicy> for TestDecayPolicy {
+  fn get() -> DecayPolicy { DECAY_POLICY.with(|v| *v.borrow()) }
+}
+
+pub fn set_decay_policy(policy: DecayPolicy) {
+  DECAY_POLICY.with(|v| *v.borrow_mut() = policy);
+}
+
+/// The commodity info before the category, the default category `0` is appended.
+#[derive(Encode, Decode)]
+pub struct OldTestInfo(pub u8);
+
+impl From<OldTestInfo> for Vec<u8> {
+  fn from(old: OldTestInfo) -> Self {
+    vec![old.0, 0]
+  }
+}
+
+parameter_types! {
+  pub const MaxDecaysPerBlock: u32 = 2;
+  pub const LifePerAsset: u64 = 10;
+  pub const MetadataDepositBase: u64 = 1;
+  pub const MetadataDepositPerByte: u64 = 1;
+  pub const KeyLimit: u32 = 8;
+  pub const ValueLimit: u32 = 16;
+  pub const MetadataLimit: u32 = 2;
+  pub const LegacyCollectionOwner: u64 = 3;
+}
+
 impl mc_nft::Config for Test {
   type Event = Event;
   type CommodityAdmin = frame_system::EnsureRoot<Self::AccountId>;
   type CommodityInfo = Vec<u8>;
+  type OldCommodityInfo = OldTestInfo;
   type CommodityLimit = MaxCommodities;
   type UserCommodityLimit = MaxCommoditiesPerUser;
-  type LifeTime = ();
+  type LifeTime = TestLifeTime;
+  type AssetLock = TestLock;
+  type DecayPolicy = TestDecayPolicy;
+  type MaxDecaysPerBlock = MaxDecaysPerBlock;
+  type FeaturedAssets = TestAssets;
+  type LifePerAsset = LifePerAsset;
+  type Currency = Balances;
+  type MetadataDepositBase = MetadataDepositBase;
+  type MetadataDepositPerByte = MetadataDepositPerByte;
+  type KeyLimit = KeyLimit;
+  type ValueLimit = ValueLimit;
+  type MetadataLimit = MetadataLimit;
+  type LegacyCollectionOwner = LegacyCollectionOwner;
+  type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-  system::GenesisConfig::default()
-    .build_storage::<Test>()
-    .unwrap()
-    .into()
+  let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+  pallet_balances::GenesisConfig::<Test> {
+    balances: vec![(1, 100), (2, 100), (3, 100)],
+  }.assimilate_storage(&mut t).unwrap();
+  // collection 0 is owned by 1 and issued by 2, its limits are above the limits of the type
+  GenesisBuild::<Test>::assimilate_storage(&mc_nft::GenesisConfig::<Test> {
+    collections: vec![(1, 2, 10, 10)],
+  }, &mut t).unwrap();
+  t.into()
 }
 
 pub type SUT = Module<Test>;
@@ -3,8 +3,13 @@
 use crate::mock::*;
 use crate::*;
 use sp_core::H256;
-use frame_support::{assert_err, assert_ok, Hashable};
-use mc_support::traits::{UniqueAssets};
+use frame_support::{
+  assert_err, assert_ok, Hashable, Blake2_128Concat, StorageHasher,
+  storage::migration::put_storage_value,
+  traits::{OnInitialize, OnRuntimeUpgrade},
+};
+use codec::Encode;
+use mc_support::traits::{UniqueAssets, FeaturedAssets};
 
 #[test]
 fn mint() {
@@ -14,11 +19,11 @@ fn mint() {
     assert_eq!(<SUT as UniqueAssets<_>>::total(), 0);
     assert_eq!(<SUT as UniqueAssets<_>>::total_for_account(&1), 0);
     assert_eq!(
-      SUT::account_for_commodity::<H256>(Vec::<u8>::default().blake2_256().into()),
+      SUT::account_for_commodity::<H256>((0u32, Vec::<u8>::default()).blake2_256().into()),
       0
     );
 
-    assert_ok!(SUT::mint(Origin::root(), 1, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
 
     assert_eq!(SUT::total(), 1);
     assert_eq!(<SUT as UniqueAssets<_>>::total(), 1);
@@ -30,11 +35,11 @@ fn mint() {
     assert_eq!(commodities_for_account.len(), 1);
     assert_eq!(
       commodities_for_account[0].0,
-      Vec::<u8>::default().blake2_256().into()
+      (0u32, Vec::<u8>::default()).blake2_256().into()
     );
     assert_eq!(commodities_for_account[0].1, Vec::<u8>::default());
     assert_eq!(
-      SUT::account_for_commodity::<H256>(Vec::<u8>::default().blake2_256().into()),
+      SUT::account_for_commodity::<H256>((0u32, Vec::<u8>::default()).blake2_256().into()),
       1
     );
   });
@@ -44,8 +49,8 @@ fn mint() {
 fn mint_err_non_admin() {
   new_test_ext().execute_with(|| {
     assert_err!(
-      SUT::mint(Origin::signed(1), 1, Vec::<u8>::default()),
-      sp_runtime::DispatchError::BadOrigin
+      SUT::mint(Origin::signed(1), 0, 1, Vec::<u8>::default()),
+      Error::<Test>::NotCollectionIssuer
     );
   });
 }
@@ -53,10 +58,10 @@ fn mint_err_non_admin() {
 #[test]
 fn mint_err_dupe() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
 
     assert_err!(
-      SUT::mint(Origin::root(), 2, Vec::<u8>::default()),
+      SUT::mint(Origin::root(), 0, 2, Vec::<u8>::default()),
       Error::<Test>::CommodityExists
     );
   });
@@ -65,11 +70,11 @@ fn mint_err_dupe() {
 #[test]
 fn mint_err_max_user() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, vec![]));
-    assert_ok!(SUT::mint(Origin::root(), 1, vec![0]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
 
     assert_err!(
-      SUT::mint(Origin::root(), 1, vec![1]),
+      SUT::mint(Origin::root(), 0, 1, vec![1]),
       Error::<Test>::TooManyCommoditiesForAccount
     );
   });
@@ -78,14 +83,14 @@ fn mint_err_max_user() {
 #[test]
 fn mint_err_max() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, vec![]));
-    assert_ok!(SUT::mint(Origin::root(), 2, vec![0]));
-    assert_ok!(SUT::mint(Origin::root(), 3, vec![1]));
-    assert_ok!(SUT::mint(Origin::root(), 4, vec![2]));
-    assert_ok!(SUT::mint(Origin::root(), 5, vec![3]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 2, vec![0]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 3, vec![1]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 4, vec![2]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 5, vec![3]));
 
     assert_err!(
-      SUT::mint(Origin::root(), 6, vec![4]),
+      SUT::mint(Origin::root(), 0, 6, vec![4]),
       Error::<Test>::TooManyCommodities
     );
   });
@@ -94,10 +99,10 @@ fn mint_err_max() {
 #[test]
 fn burn() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
     assert_ok!(SUT::burn(
       Origin::signed(1),
-      Vec::<u8>::default().blake2_256().into()
+      (0u32, Vec::<u8>::default()).blake2_256().into()
     ));
 
     assert_eq!(SUT::total(), 0);
@@ -105,7 +110,7 @@ fn burn() {
     assert_eq!(SUT::total_for_account(1), 0);
     assert_eq!(SUT::commodities_for_account::<u64>(1), vec![]);
     assert_eq!(
-      SUT::account_for_commodity::<H256>(Vec::<u8>::default().blake2_256().into()),
+      SUT::account_for_commodity::<H256>((0u32, Vec::<u8>::default()).blake2_256().into()),
       0
     );
   });
@@ -114,10 +119,10 @@ fn burn() {
 #[test]
 fn burn_err_not_owner() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
 
     assert_err!(
-      SUT::burn(Origin::signed(2), Vec::<u8>::default().blake2_256().into()),
+      SUT::burn(Origin::signed(2), (0u32, Vec::<u8>::default()).blake2_256().into()),
       Error::<Test>::NotCommodityOwner
     );
   });
@@ -127,7 +132,7 @@ fn burn_err_not_owner() {
 fn burn_err_not_exist() {
   new_test_ext().execute_with(|| {
     assert_err!(
-      SUT::burn(Origin::signed(1), Vec::<u8>::default().blake2_256().into()),
+      SUT::burn(Origin::signed(1), (0u32, Vec::<u8>::default()).blake2_256().into()),
       Error::<Test>::NotCommodityOwner
     );
   });
@@ -136,11 +141,11 @@ fn burn_err_not_exist() {
 #[test]
 fn transfer() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
     assert_ok!(SUT::transfer(
       Origin::signed(1),
       2,
-      Vec::<u8>::default().blake2_256().into()
+      (0u32, Vec::<u8>::default()).blake2_256().into()
     ));
 
     assert_eq!(SUT::total(), 1);
@@ -152,11 +157,11 @@ fn transfer() {
     assert_eq!(commodities_for_account.len(), 1);
     assert_eq!(
       commodities_for_account[0].0,
-      Vec::<u8>::default().blake2_256().into()
+      (0u32, Vec::<u8>::default()).blake2_256().into()
     );
     assert_eq!(commodities_for_account[0].1, Vec::<u8>::default());
     assert_eq!(
-      SUT::account_for_commodity::<H256>(Vec::<u8>::default().blake2_256().into()),
+      SUT::account_for_commodity::<H256>((0u32, Vec::<u8>::default()).blake2_256().into()),
       2
     );
   });
@@ -165,13 +170,13 @@ fn transfer() {
 #[test]
 fn transfer_err_not_owner() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
 
     assert_err!(
       SUT::transfer(
         Origin::signed(0),
         2,
-        Vec::<u8>::default().blake2_256().into()
+        (0u32, Vec::<u8>::default()).blake2_256().into()
       ),
       Error::<Test>::NotCommodityOwner
     );
@@ -185,7 +190,7 @@ fn transfer_err_not_exist() {
       SUT::transfer(
         Origin::signed(1),
         2,
-        Vec::<u8>::default().blake2_256().into()
+        (0u32, Vec::<u8>::default()).blake2_256().into()
       ),
       Error::<Test>::NotCommodityOwner
     );
@@ -195,11 +200,11 @@ fn transfer_err_not_exist() {
 #[test]
 fn transfer_err_max_user() {
   new_test_ext().execute_with(|| {
-    assert_ok!(SUT::mint(Origin::root(), 1, vec![0]));
-    assert_ok!(SUT::mint(Origin::root(), 1, vec![1]));
-    assert_ok!(SUT::mint(Origin::root(), 2, Vec::<u8>::default()));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![1]));
+    assert_ok!(SUT::mint(Origin::root(), 0, 2, Vec::<u8>::default()));
     assert_eq!(
-      SUT::account_for_commodity::<H256>(Vec::<u8>::default().blake2_256().into()),
+      SUT::account_for_commodity::<H256>((0u32, Vec::<u8>::default()).blake2_256().into()),
       2
     );
 
@@ -207,9 +212,573 @@ fn transfer_err_max_user() {
       SUT::transfer(
         Origin::signed(2),
         1,
-        Vec::<u8>::default().blake2_256().into()
+        (0u32, Vec::<u8>::default()).blake2_256().into()
       ),
       Error::<Test>::TooManyCommoditiesForAccount
     );
   });
 }
+
+#[test]
+fn locked_commodity_cannot_be_transferred_or_burned() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, Vec::<u8>::default()).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, Vec::<u8>::default()));
+    set_locked(commodity_id, true);
+
+    assert_err!(
+      SUT::transfer(Origin::signed(1), 2, commodity_id),
+      Error::<Test>::CommodityLocked
+    );
+    assert_err!(
+      SUT::burn(Origin::signed(1), commodity_id),
+      Error::<Test>::CommodityLocked
+    );
+    assert_eq!(SUT::account_for_commodity::<H256>(commodity_id), 1);
+
+    set_locked(commodity_id, false);
+    assert_ok!(SUT::transfer(Origin::signed(1), 2, commodity_id));
+    assert_ok!(SUT::burn(Origin::signed(2), commodity_id));
+  });
+}
+
+#[test]
+fn commodity_should_decay() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    let burned: H256 = (0u32, vec![0u8]).blake2_256().into();
+    let flagged: H256 = (0u32, vec![1u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_eq!(SUT::decay_queue(101), Some(vec![burned]));
+
+    SUT::on_initialize(101);
+    assert_eq!(SUT::total(), 0);
+    assert!(System::events().iter().any(|record| record.event ==
+      crate::mock::Event::mc_nft(crate::Event::Decayed(burned, true))
+    ));
+
+    // flagged ones stay with the owner but decayed
+    set_decay_policy(DecayPolicy::Flag);
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![1]));
+    SUT::on_initialize(101);
+    assert_eq!(SUT::account_for_commodity::<H256>(flagged), 1);
+    assert!(<SUT as UniqueAssets<_>>::is_decayed(&flagged));
+    set_decay_policy(DecayPolicy::Burn);
+  });
+}
+
+#[test]
+fn locked_commodity_should_be_flagged() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    set_locked(commodity_id, true);
+
+    SUT::on_initialize(101);
+    assert_eq!(SUT::account_for_commodity::<H256>(commodity_id), 1);
+    assert!(<SUT as UniqueAssets<_>>::is_decayed(&commodity_id));
+    set_locked(commodity_id, false);
+  });
+}
+
+#[test]
+fn decay_queue_should_be_bounded() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    for i in 0..3u8 {
+      assert_ok!(SUT::mint(Origin::root(), 0, i as u64, vec![i]));
+    }
+
+    SUT::on_initialize(101);
+    assert_eq!(SUT::total(), 1);
+    assert_eq!(SUT::decay_queue(102), Some(vec![(0u32, vec![2u8]).blake2_256().into()]));
+
+    SUT::on_initialize(102);
+    assert_eq!(SUT::total(), 0);
+    assert_eq!(SUT::decay_queue(102), None);
+  });
+}
+
+#[test]
+fn extend_life_should_work() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_ok!(TestAssets::mint(0, &1, 5));
+
+    assert_err!(SUT::extend_life(Origin::signed(2), commodity_id, 0, 3), Error::<Test>::NotCommodityOwner);
+    assert_err!(SUT::extend_life(Origin::signed(1), commodity_id, 1, 3), Error::<Test>::AssetNotUsed);
+    assert_err!(SUT::extend_life(Origin::signed(1), commodity_id, 0, 0), Error::<Test>::ZeroExtension);
+    assert_ok!(SUT::extend_life(Origin::signed(1), commodity_id, 0, 3));
+    assert_eq!(TestAssets::balance(0, 1), 2);
+    assert_eq!(SUT::decay_queue(101), None);
+    assert_eq!(SUT::decay_queue(131), Some(vec![commodity_id]));
+
+    SUT::on_initialize(101);
+    assert_eq!(SUT::total(), 1);
+    SUT::on_initialize(131);
+    assert_eq!(SUT::total(), 0);
+  });
+}
+
+#[test]
+fn runtime_upgrade_should_do_nothing_on_new_chain() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_eq!(SUT::storage_version(), Releases::V6);
+
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::storage_version(), Releases::V6);
+    assert_eq!(SUT::commodities_for_account::<u64>(1), vec![(commodity_id, vec![0])]);
+    assert_eq!(SUT::collection_for_commodity(commodity_id), Some(0));
+    assert_eq!(SUT::next_collection_id(), 1);
+    assert_eq!(SUT::decay_queue(101), Some(vec![commodity_id]));
+  });
+}
+
+#[test]
+fn migrate_to_category_should_work() {
+  new_test_ext().execute_with(|| {
+    let first: H256 = vec![1u8].blake2_256().into();
+    put_storage_value(
+      b"TemplateModule",
+      b"CommoditiesForAccount",
+      &Blake2_128Concat::hash(&1u64.encode()),
+      vec![(first, OldTestInfo(1))],
+    );
+
+    migrations::migrate_to_category::<Test>();
+    assert_eq!(
+      frame_support::storage::migration::get_storage_value::<Vec<(H256, Vec<u8>)>>(
+        b"TemplateModule",
+        b"CommoditiesForAccount",
+        &Blake2_128Concat::hash(&1u64.encode()),
+      ),
+      Some(vec![(first, vec![1u8, 0])])
+    );
+  });
+}
+
+#[test]
+fn migrate_to_double_map_should_work() {
+  new_test_ext().execute_with(|| {
+    let first: H256 = vec![1u8].blake2_256().into();
+    let second: H256 = vec![2u8].blake2_256().into();
+    let mut commodities = vec![(first, vec![1u8]), (second, vec![2u8])];
+    commodities.sort();
+    AccountForCommodity::<Test>::insert(first, 1);
+    AccountForCommodity::<Test>::insert(second, 1);
+    put_storage_value(
+      b"TemplateModule",
+      b"CommoditiesForAccount",
+      &Blake2_128Concat::hash(&1u64.encode()),
+      commodities.clone(),
+    );
+    StorageVersion::<Test>::put(Releases::V2);
+
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::storage_version(), Releases::V6);
+    assert_eq!(SUT::commodities_for_account::<u64>(1), commodities);
+    assert_eq!(<SUT as UniqueAssets<_>>::assets_for_account(&1), commodities);
+    assert_eq!(SUT::commodity_info(first), Some(vec![1u8]));
+    assert_eq!(
+      frame_support::storage::migration::get_storage_value::<Vec<(H256, Vec<u8>)>>(
+        b"TemplateModule",
+        b"CommoditiesForAccount",
+        &Blake2_128Concat::hash(&1u64.encode()),
+      ),
+      None
+    );
+    // the existing commodities are moved into a legacy collection
+    assert_eq!(SUT::collection_for_commodity(first), Some(1));
+    assert_eq!(<SUT as UniqueAssets<_>>::total_in_collection(1), 2);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(1, &1), 2);
+    assert_eq!(SUT::next_collection_id(), 2);
+  });
+}
+
+#[test]
+fn commodities_of_first_release_should_survive_upgrade() {
+  new_test_ext().execute_with(|| {
+    let first: H256 = vec![1u8].blake2_256().into();
+    let second: H256 = vec![2u8].blake2_256().into();
+    let mut commodities = vec![(first, OldTestInfo(1)), (second, OldTestInfo(2))];
+    commodities.sort_by_key(|(commodity_id, _)| *commodity_id);
+    AccountForCommodity::<Test>::insert(first, 1);
+    AccountForCommodity::<Test>::insert(second, 1);
+    put_storage_value(
+      b"TemplateModule",
+      b"CommoditiesForAccount",
+      &Blake2_128Concat::hash(&1u64.encode()),
+      commodities,
+    );
+    StorageVersion::<Test>::put(Releases::V1);
+
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::storage_version(), Releases::V6);
+    assert_eq!(SUT::commodity_info(first), Some(vec![1u8, 0]));
+    assert_eq!(SUT::commodity_info(second), Some(vec![2u8, 0]));
+    assert_eq!(<SUT as UniqueAssets<_>>::assets_for_account(&1).len(), 2);
+    assert_eq!(SUT::collection_for_commodity(first), Some(1));
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(1, &1), 2);
+  });
+}
+
+#[test]
+fn migrate_to_collections_should_skip_collected_commodities() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    let collected: H256 = (0u32, vec![0u8]).blake2_256().into();
+    let legacy: H256 = vec![1u8].blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    AccountForCommodity::<Test>::insert(legacy, 1);
+    StorageVersion::<Test>::put(Releases::V3);
+
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::collection_for_commodity(collected), Some(0));
+    assert_eq!(<SUT as UniqueAssets<_>>::total_in_collection(0), 1);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(0, &1), 1);
+    assert_eq!(SUT::collection_for_commodity(legacy), Some(1));
+    assert_eq!(<SUT as UniqueAssets<_>>::total_in_collection(1), 1);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(1, &1), 1);
+    assert_eq!(SUT::next_collection_id(), 2);
+
+    // nothing to collect, no more legacy collection
+    StorageVersion::<Test>::put(Releases::V3);
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::next_collection_id(), 2);
+  });
+}
+
+#[test]
+fn migrate_to_collections_should_create_first_collection() {
+  new_test_ext().execute_with(|| {
+    Collections::<Test>::remove(0);
+    NextCollectionId::<Test>::put(0);
+    StorageVersion::<Test>::put(Releases::V3);
+
+    SUT::on_runtime_upgrade();
+    let collection = SUT::collection(0).unwrap();
+    assert_eq!(collection.owner, 3);
+    assert_eq!(collection.issuer, 3);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_in_collection(0), 0);
+    assert_eq!(SUT::next_collection_id(), 1);
+  });
+}
+
+#[test]
+fn burn_and_transfer_err_not_exist_through_trait() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, Vec::<u8>::default()).blake2_256().into();
+    assert_err!(<SUT as UniqueAssets<_>>::burn(&commodity_id), Error::<Test>::NonexistentCommodity);
+    assert_err!(<SUT as UniqueAssets<_>>::transfer(&2, &commodity_id), Error::<Test>::NonexistentCommodity);
+  });
+}
+
+#[test]
+fn inconsistent_ownership_should_not_panic() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, Vec::<u8>::default()).blake2_256().into();
+    // an owner without the commodity in its inventory
+    AccountForCommodity::<Test>::insert(commodity_id, 1);
+
+    assert_err!(SUT::burn(Origin::signed(1), commodity_id), Error::<Test>::NonexistentCommodity);
+    assert_err!(SUT::transfer(Origin::signed(1), 2, commodity_id), Error::<Test>::NonexistentCommodity);
+    assert_eq!(SUT::total(), 0);
+    assert_eq!(SUT::burned(), 0);
+    assert_eq!(SUT::total_for_account(1), 0);
+    assert_eq!(SUT::total_for_account(2), 0);
+  });
+}
+
+#[test]
+fn burn_should_not_depend_on_default_info() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, vec![9u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![9u8]));
+    assert_ok!(SUT::transfer(Origin::signed(1), 2, commodity_id));
+    assert_eq!(SUT::commodities_for_account::<u64>(2), vec![(commodity_id, vec![9u8])]);
+
+    assert_ok!(SUT::burn(Origin::signed(2), commodity_id));
+    assert_eq!(SUT::total(), 0);
+    assert_eq!(SUT::commodity_info(commodity_id), None);
+    assert_eq!(SUT::commodities_for_account::<u64>(2), vec![]);
+  });
+}
+
+#[test]
+fn create_collection_should_work() {
+  new_test_ext().execute_with(|| {
+    assert_err!(
+      SUT::create_collection(Origin::signed(1), 1, 1, 1, 1),
+      sp_runtime::DispatchError::BadOrigin
+    );
+    assert_ok!(SUT::create_collection(Origin::root(), 3, 3, 1, 1));
+    assert_eq!(SUT::next_collection_id(), 2);
+    assert_eq!(SUT::collection(1), Some(CollectionInfo {
+      owner: 3,
+      issuer: 3,
+      max_supply: 1,
+      max_per_user: 1,
+      supply: 0,
+    }));
+    assert_err!(
+      SUT::mint(Origin::root(), 2, 1, Vec::<u8>::default()),
+      Error::<Test>::UnknownCollection
+    );
+  });
+}
+
+#[test]
+fn collection_owner_should_manage_issuer_and_meta() {
+  new_test_ext().execute_with(|| {
+    assert_err!(SUT::set_collection_issuer(Origin::signed(2), 0, 3), Error::<Test>::NotCollectionOwner);
+    assert_err!(SUT::set_collection_issuer(Origin::signed(1), 1, 3), Error::<Test>::UnknownCollection);
+    assert_ok!(SUT::set_collection_issuer(Origin::signed(1), 0, 3));
+
+    assert_err!(
+      SUT::mint(Origin::signed(2), 0, 1, Vec::<u8>::default()),
+      Error::<Test>::NotCollectionIssuer
+    );
+    assert_ok!(SUT::mint(Origin::signed(3), 0, 1, Vec::<u8>::default()));
+    assert_eq!(SUT::total_for_account(1), 1);
+
+    assert_err!(
+      SUT::add_collection_meta(Origin::signed(3), 0, b"tradable".to_vec(), MetaValue::Bool(true)),
+      Error::<Test>::NotCollectionOwner
+    );
+    assert_ok!(SUT::add_collection_meta(Origin::signed(1), 0, b"tradable".to_vec(), MetaValue::Bool(true)));
+    assert_ok!(SUT::add_collection_meta(Origin::signed(1), 0, b"tradable".to_vec(), MetaValue::Bool(false)));
+    // key of 8 bytes and value of 2 bytes
+    assert_eq!(SUT::collection_meta(0), vec![MetaKeyValue {
+      key: b"tradable".to_vec(),
+      value: MetaValue::Bool(false),
+      locked: false,
+      depositor: 1,
+      deposit: 11,
+    }]);
+    assert_eq!(Balances::reserved_balance(1), 11);
+
+    assert_err!(
+      SUT::remove_collection_meta(Origin::signed(1), 0, b"unknown".to_vec()),
+      Error::<Test>::UnknownMetaKey
+    );
+    assert_ok!(SUT::remove_collection_meta(Origin::signed(1), 0, b"tradable".to_vec()));
+    assert_eq!(SUT::collection_meta(0), vec![]);
+    assert_eq!(Balances::reserved_balance(1), 0);
+  });
+}
+
+#[test]
+fn collection_limits_should_apply() {
+  new_test_ext().execute_with(|| {
+    assert_ok!(SUT::create_collection(Origin::root(), 1, 1, 2, 1));
+    assert_ok!(SUT::mint(Origin::root(), 1, 1, vec![0]));
+    assert_err!(
+      SUT::mint(Origin::root(), 1, 1, vec![1]),
+      Error::<Test>::TooManyCollectionCommoditiesForAccount
+    );
+    assert_ok!(SUT::mint(Origin::root(), 1, 2, vec![1]));
+    assert_err!(
+      SUT::mint(Origin::root(), 1, 3, vec![2]),
+      Error::<Test>::TooManyCommoditiesInCollection
+    );
+
+    let commodity_id: H256 = (1u32, vec![0u8]).blake2_256().into();
+    assert_err!(
+      SUT::transfer(Origin::signed(1), 2, commodity_id),
+      Error::<Test>::TooManyCollectionCommoditiesForAccount
+    );
+    // the other collection has its own limits
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![1]));
+
+    assert_ok!(SUT::burn(Origin::signed(1), commodity_id));
+    assert_eq!(<SUT as UniqueAssets<_>>::total_in_collection(1), 1);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(1, &1), 0);
+    assert_eq!(SUT::collection_for_commodity(commodity_id), None);
+    assert_ok!(SUT::mint(Origin::root(), 1, 3, vec![2]));
+  });
+}
+
+#[test]
+fn same_info_should_live_in_different_collections() {
+  new_test_ext().execute_with(|| {
+    assert_ok!(SUT::create_collection(Origin::root(), 1, 1, 10, 10));
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_ok!(SUT::mint(Origin::root(), 1, 1, vec![0]));
+    assert_err!(SUT::mint(Origin::root(), 1, 1, vec![0]), Error::<Test>::CommodityExists);
+
+    let in_first: H256 = (0u32, vec![0u8]).blake2_256().into();
+    let in_second: H256 = (1u32, vec![0u8]).blake2_256().into();
+    assert_eq!(<SUT as UniqueAssets<_>>::collection_of(&in_first), Some(0));
+    assert_eq!(<SUT as UniqueAssets<_>>::collection_of(&in_second), Some(1));
+    assert_eq!(SUT::commodities_for_account::<u64>(1).len(), 2);
+    assert_eq!(
+      <SUT as UniqueAssets<_>>::assets_for_account_in_collection(1, &1),
+      vec![(in_second, vec![0u8])]
+    );
+
+    assert_ok!(SUT::transfer(Origin::signed(1), 2, in_second));
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(1, &1), 0);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(1, &2), 1);
+    assert_eq!(<SUT as UniqueAssets<_>>::total_for_account_in_collection(0, &1), 1);
+  });
+}
+
+#[test]
+fn add_meta_should_be_typed_and_bounded() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+
+    assert_err!(
+      SUT::add_meta(Origin::signed(2), commodity_id, b"level".to_vec(), MetaValue::U32(1), false),
+      Error::<Test>::NotCommodityOwner
+    );
+    assert_err!(
+      SUT::add_meta(Origin::signed(1), commodity_id, b"too long key".to_vec(), MetaValue::U32(1), false),
+      Error::<Test>::BadMetadata
+    );
+    assert_err!(
+      SUT::add_meta(Origin::signed(1), commodity_id, b"name".to_vec(), MetaValue::Bytes(vec![0; 17]), false),
+      Error::<Test>::BadMetadata
+    );
+
+    // key of 5 bytes and value of 5 bytes
+    assert_ok!(SUT::add_meta(Origin::signed(1), commodity_id, b"level".to_vec(), MetaValue::U32(1), false));
+    assert_eq!(Balances::reserved_balance(1), 11);
+    // key of 5 bytes and value of 17 bytes, the old deposit is returned
+    assert_ok!(SUT::add_meta(Origin::signed(1), commodity_id, b"level".to_vec(), MetaValue::U128(2), false));
+    assert_eq!(Balances::reserved_balance(1), 23);
+    assert_ok!(SUT::add_meta(Origin::signed(1), commodity_id, b"seal".to_vec(), MetaValue::Hash(H256::zero()), false));
+    assert_err!(
+      SUT::add_meta(Origin::signed(1), commodity_id, b"name".to_vec(), MetaValue::Bool(true), false),
+      Error::<Test>::TooManyMeta
+    );
+    assert_eq!(SUT::meta_data(commodity_id).len(), 2);
+    assert_eq!(SUT::meta_data(commodity_id)[0].value, MetaValue::U128(2));
+  });
+}
+
+#[test]
+fn locked_meta_should_be_edited_by_issuer_only() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+
+    assert_err!(
+      SUT::add_meta(Origin::signed(1), commodity_id, b"origin".to_vec(), MetaValue::U32(7), true),
+      Error::<Test>::MetaLocked
+    );
+    // 2 is the issuer of collection 0
+    assert_ok!(SUT::add_meta(Origin::signed(2), commodity_id, b"origin".to_vec(), MetaValue::U32(7), true));
+    assert_eq!(Balances::reserved_balance(2), 12);
+    assert!(SUT::meta_data(commodity_id)[0].locked);
+
+    assert_err!(
+      SUT::add_meta(Origin::signed(1), commodity_id, b"origin".to_vec(), MetaValue::U32(8), false),
+      Error::<Test>::MetaLocked
+    );
+    assert_err!(SUT::remove_meta(Origin::signed(1), commodity_id, b"origin".to_vec()), Error::<Test>::MetaLocked);
+    assert_err!(
+      SUT::add_meta(Origin::signed(2), commodity_id, b"name".to_vec(), MetaValue::Bool(true), false),
+      Error::<Test>::NotCommodityOwner
+    );
+
+    // the locked meta follows the commodity
+    assert_ok!(SUT::transfer(Origin::signed(1), 3, commodity_id));
+    assert_ok!(SUT::add_meta(Origin::signed(3), commodity_id, b"name".to_vec(), MetaValue::Bool(true), false));
+    assert_err!(SUT::remove_meta(Origin::signed(3), commodity_id, b"level".to_vec()), Error::<Test>::UnknownMetaKey);
+    assert_ok!(SUT::remove_meta(Origin::signed(2), commodity_id, b"origin".to_vec()));
+    assert_eq!(Balances::reserved_balance(2), 0);
+    assert_eq!(SUT::meta_data(commodity_id).len(), 1);
+  });
+}
+
+#[test]
+fn clear_meta_should_remove_editable_meta() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_ok!(SUT::add_meta(Origin::signed(1), commodity_id, b"name".to_vec(), MetaValue::Bool(true), false));
+    assert_ok!(SUT::add_meta(Origin::signed(2), commodity_id, b"origin".to_vec(), MetaValue::U32(7), true));
+
+    assert_err!(SUT::clear_meta(Origin::signed(3), commodity_id), Error::<Test>::NotCommodityOwner);
+    assert_ok!(SUT::clear_meta(Origin::signed(1), commodity_id));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(SUT::meta_data(commodity_id).len(), 1);
+    assert_ok!(SUT::clear_meta(Origin::signed(2), commodity_id));
+    assert_eq!(Balances::reserved_balance(2), 0);
+    assert!(!NftMeta::<Test>::contains_key(commodity_id));
+  });
+}
+
+#[test]
+fn burn_should_return_meta_deposits() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    assert_ok!(SUT::add_meta(Origin::signed(1), commodity_id, b"name".to_vec(), MetaValue::Bool(true), false));
+    assert_ok!(SUT::add_meta(Origin::signed(2), commodity_id, b"origin".to_vec(), MetaValue::U32(7), true));
+
+    assert_ok!(SUT::burn(Origin::signed(1), commodity_id));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(Balances::reserved_balance(2), 0);
+    assert!(!NftMeta::<Test>::contains_key(commodity_id));
+  });
+}
+
+#[test]
+fn migrate_to_typed_meta_should_work() {
+  new_test_ext().execute_with(|| {
+    let commodity_id: H256 = (0u32, vec![0u8]).blake2_256().into();
+    assert_ok!(SUT::mint(Origin::root(), 0, 1, vec![0]));
+    put_storage_value(
+      b"TemplateModule",
+      b"NftMeta",
+      commodity_id.as_bytes(),
+      vec![(b"tradable".to_vec(), true)],
+    );
+    StorageVersion::<Test>::put(Releases::V4);
+
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::storage_version(), Releases::V6);
+    assert_eq!(SUT::meta_data(commodity_id), vec![MetaKeyValue {
+      key: b"tradable".to_vec(),
+      value: MetaValue::Bool(true),
+      locked: false,
+      depositor: 1,
+      deposit: 0,
+    }]);
+  });
+}
+
+#[test]
+fn migrate_to_decay_queue_should_work() {
+  new_test_ext().execute_with(|| {
+    System::set_block_number(150);
+    let decaying = H256::repeat_byte(1);
+    let overdue = H256::repeat_byte(2);
+    let endless = H256::repeat_byte(3);
+    // generated_at, decayed_at
+    put_storage_value(b"TemplateModule", b"NftExistInfo", decaying.as_bytes(), (100u64, 200u64));
+    put_storage_value(b"TemplateModule", b"NftExistInfo", overdue.as_bytes(), (1u64, 101u64));
+    put_storage_value(b"TemplateModule", b"NftExistInfo", endless.as_bytes(), (1u64, 1u64));
+    StorageVersion::<Test>::put(Releases::V5);
+
+    SUT::on_runtime_upgrade();
+    assert_eq!(SUT::storage_version(), Releases::V6);
+    assert_eq!(SUT::exist_info(decaying), ExistInfo { generated_at: 100, decayed_at: 200, is_decayed: false });
+    assert_eq!(SUT::exist_info(endless), ExistInfo { generated_at: 1, decayed_at: 0, is_decayed: false });
+    assert_eq!(SUT::decay_queue(200), Some(vec![decaying]));
+    assert_eq!(SUT::decay_queue(151), Some(vec![overdue]));
+
+    set_decay_policy(DecayPolicy::Flag);
+    SUT::on_initialize(151);
+    assert!(SUT::exist_info(overdue).is_decayed);
+  });
+}
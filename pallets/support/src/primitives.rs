@@ -91,6 +91,18 @@ impl From<&[u8;4]> for FeatureElements {
 	}
 }
 
+impl FeatureElements {
+	/// all the hues in the elements
+	pub fn hues(&self) -> Vec<FeatureHue> {
+		match self {
+			Self::One(one) => vec![one.clone()],
+			Self::Two(one, two) => vec![one.clone(), two.clone()],
+			Self::Three(one, two, three) => vec![one.clone(), two.clone(), three.clone()],
+			Self::Four(one, two, three, four) => vec![one.clone(), two.clone(), three.clone(), four.clone()],
+		}
+	}
+}
+
 impl Default for FeatureElements {
 	fn default() -> Self { Self::One(FeatureHue::Green) }
 }
@@ -225,14 +237,29 @@ impl AssetFeature {
 	}
 }
 
-#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Ord, PartialOrd)]
 pub enum UniqueAssetCategory {
 	Equipment,
+	Weapon,
+	Armor,
+	Accessory,
 }
 impl Default for UniqueAssetCategory {
 	fn default() -> Self { Self::Equipment }
 }
 
+impl UniqueAssetCategory {
+	/// The number of assets in this category an actor can equip at the same time.
+	pub fn equipment_slots(&self) -> u32 {
+		match self {
+			Self::Equipment => 4,
+			Self::Weapon => 2,
+			Self::Armor => 1,
+			Self::Accessory => 2,
+		}
+	}
+}
+
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, Ord, PartialOrd)]
 pub struct UniqueAssetInfo<
 	FormulaId: Encode + Decode + Clone + Default + Eq + PartialEq,
@@ -241,6 +268,31 @@ pub struct UniqueAssetInfo<
     pub name: Vec<u8>,
     pub formula_id: FormulaId,
 	pub mint_at: BlockNumber,
+	/// the category of formula which minted the asset
+	pub category: UniqueAssetCategory,
+}
+
+/// The unique asset info before the category was added, kept for storage migrations.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct OldUniqueAssetInfo<FormulaId, BlockNumber> {
+	pub name: Vec<u8>,
+	pub formula_id: FormulaId,
+	pub mint_at: BlockNumber,
+}
+
+impl<
+	FormulaId: Encode + Decode + Clone + Default + Eq + PartialEq,
+	BlockNumber: Encode + Decode + Default + Eq + PartialEq,
+> From<OldUniqueAssetInfo<FormulaId, BlockNumber>> for UniqueAssetInfo<FormulaId, BlockNumber> {
+	/// The old assets are in the default `Equipment` category.
+	fn from(old: OldUniqueAssetInfo<FormulaId, BlockNumber>) -> Self {
+		UniqueAssetInfo {
+			name: old.name,
+			formula_id: old.formula_id,
+			mint_at: old.mint_at,
+			category: UniqueAssetCategory::default(),
+		}
+	}
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default)]
@@ -7,8 +7,10 @@
 use sp_std::{
 	fmt::Debug,
 	hash::Hash,
+	marker::PhantomData,
 };
 use sp_runtime::{
+	Percent,
 	traits::{ AtLeast32BitUnsigned },
 };
 use frame_support::{
@@ -16,20 +18,27 @@ use frame_support::{
 	traits::Get,
 };
 use frame_support::pallet_prelude::*;
+use frame_system::RawOrigin;
 
 use sp_std::vec::Vec;
-use super::primitives::{ AssetFeature };
+use super::primitives::{ AssetFeature, Formula, FeatureHue, FeatureRankedLevel, FeatureLevel };
 
 
 pub trait ManagerAccessor<AccountId>: Sized {
 	fn get_owner_id() -> AccountId;
 	// Default impls
-	/// Can thaw tokens, force transfers and burn tokens from any account.
+	/// Can force transfers of tokens from any account.
 	fn is_admin(_: &AccountId) -> bool { false }
-	/// Can mint tokens.
+	/// Can mint tokens and burn tokens from any account.
 	fn is_issuer(_: &AccountId) -> bool { false }
-	/// Can freeze tokens.
+	/// Can freeze and thaw tokens.
 	fn is_freezer(_: &AccountId) -> bool { false }
+	/// Can host dungeon instances.
+	fn is_server(_: &AccountId) -> bool { false }
+	/// Can design formulas.
+	fn is_designer(_: &AccountId) -> bool { false }
+	/// Can propose treasury spends.
+	fn is_treasurer(_: &AccountId) -> bool { false }
 }
 /// default implement for test
 impl ManagerAccessor<u64> for () {
@@ -38,20 +47,110 @@ impl ManagerAccessor<u64> for () {
 	}
 }
 
+/// A role of managers
+pub trait ManagerRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool;
+}
+
+/// The admin role of managers
+pub struct AdminRole;
+impl ManagerRole for AdminRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool {
+		Accessor::is_admin(who)
+	}
+}
+
+/// The issuer role of managers
+pub struct IssuerRole;
+impl ManagerRole for IssuerRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool {
+		Accessor::is_issuer(who)
+	}
+}
+
+/// The freezer role of managers
+pub struct FreezerRole;
+impl ManagerRole for FreezerRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool {
+		Accessor::is_freezer(who)
+	}
+}
+
+/// The dungeon server role of managers
+pub struct ServerRole;
+impl ManagerRole for ServerRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool {
+		Accessor::is_server(who)
+	}
+}
+
+/// The formula designer role of managers
+pub struct DesignerRole;
+impl ManagerRole for DesignerRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool {
+		Accessor::is_designer(who)
+	}
+}
+
+/// The treasurer role of managers
+pub struct TreasurerRole;
+impl ManagerRole for TreasurerRole {
+	fn is_granted<AccountId, Accessor: ManagerAccessor<AccountId>>(who: &AccountId) -> bool {
+		Accessor::is_treasurer(who)
+	}
+}
+
+/// Ensure the origin is signed by a manager of `Accessor` granted `Role`, the signer is returned.
+///
+/// Use `EnsureOneOf` in runtime to also accept root or a collective.
+pub struct EnsureManager<Accessor, AccountId, Role = AdminRole>(PhantomData<(Accessor, AccountId, Role)>);
+impl<
+	O: Into<Result<RawOrigin<AccountId>, O>> + From<RawOrigin<AccountId>>,
+	Accessor: ManagerAccessor<AccountId>,
+	AccountId: Default,
+	Role: ManagerRole,
+> EnsureOrigin<O> for EnsureManager<Accessor, AccountId, Role> {
+	type Success = AccountId;
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		o.into().and_then(|o| match o {
+			RawOrigin::Signed(who) if Role::is_granted::<AccountId, Accessor>(&who) => Ok(who),
+			r => Err(O::from(r)),
+		})
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> O {
+		O::from(RawOrigin::Signed(Default::default()))
+	}
+}
+
+/// Random hashes, every call in a block should get a different one.
 pub trait RandomHash<Hash> {
-	fn generate() -> Hash;
+	/// A random hash without subject.
+	fn generate() -> Hash {
+		Self::generate_by_subject(&[])
+	}
+	/// A random hash mixed with a caller-provided subject.
+	fn generate_by_subject(subject: &[u8]) -> Hash;
 }
 impl RandomHash<u32> for () {
-	fn generate() -> u32 { 0 }
+	fn generate_by_subject(_: &[u8]) -> u32 { 0 }
 }
 
+/// Random numbers, every call in a block should get a different one.
 pub trait RandomNumber<T> {
+	/// A random number mixed with the seed.
 	fn generate_by_seed(seed: T) -> T;
-	fn generate_in_range(total: T) -> T;
+	/// A random number in `[0, total)` without subject.
+	fn generate_in_range(total: T) -> T {
+		Self::generate_in_range_by_subject(&[], total)
+	}
+	/// A random number in `[0, total)` mixed with a caller-provided subject.
+	fn generate_in_range_by_subject(subject: &[u8], total: T) -> T;
 }
 impl RandomNumber<u32> for () {
 	fn generate_by_seed(_: u32) -> u32 { 0 }
-	fn generate_in_range(_: u32) -> u32 { 0 }
+	fn generate_in_range_by_subject(_: &[u8], _: u32) -> u32 { 0 }
 }
 
 // some thing with life
@@ -62,6 +161,52 @@ impl LifeTime<u64> for () {
 	fn base_age(_: u32) -> u64 { 0 }
 }
 
+/// The experience required to level up.
+pub trait ExperienceCurve {
+	/// The experience required to level up from `level` to the next level.
+	fn required_experience(level: u32) -> u32;
+}
+impl ExperienceCurve for () {
+	fn required_experience(_: u32) -> u32 { u32::MAX }
+}
+
+/// Something gaining experience, like the actor of an account.
+pub trait Experience<AccountId> {
+	/// Award some experience to `who`, the number of levels gained is returned.
+	fn award_experience(who: &AccountId, amount: u32) -> u32;
+}
+impl<AccountId> Experience<AccountId> for () {
+	fn award_experience(_: &AccountId, _: u32) -> u32 { 0 }
+}
+
+/// Calculate the final success rate of crafting by a formula.
+pub trait CraftingOdds<FormulaId, Balance> where
+	FormulaId: Encode + Decode + Clone + Eq + PartialEq,
+	Balance: Encode + Decode + AtLeast32BitUnsigned + Default + Copy,
+{
+	/// - `formula`: The executed formula.
+	/// - `feature_amounts`: The aggregated amounts of each hue in the consumed assets.
+	/// - `feature_levels`: The `saturation` and `lightness` of each consumed asset.
+	fn success_rate(
+		formula: &Formula<FormulaId, Balance>,
+		feature_amounts: &[(FeatureHue, Balance)],
+		feature_levels: &[(FeatureRankedLevel, FeatureLevel)],
+	) -> Percent;
+}
+/// default implement for test, just use the rate of formula
+impl<FormulaId, Balance> CraftingOdds<FormulaId, Balance> for () where
+	FormulaId: Encode + Decode + Clone + Eq + PartialEq,
+	Balance: Encode + Decode + AtLeast32BitUnsigned + Default + Copy,
+{
+	fn success_rate(
+		formula: &Formula<FormulaId, Balance>,
+		_: &[(FeatureHue, Balance)],
+		_: &[(FeatureRankedLevel, FeatureLevel)],
+	) -> Percent {
+		formula.rate_of_success
+	}
+}
+
 /// An interface over a set of featured assets.
 pub trait FeaturedAssets<AccountId> {
 	/// The type used to identify featured assets.
@@ -102,6 +247,15 @@ pub trait FeaturedAssets<AccountId> {
 }
 
 
+/// Lock of unique assets, the locked assets can not be transferred or burned.
+pub trait UniqueAssetLock<AccountId, AssetId> {
+	/// Whether the asset owned by `owner` is locked.
+	fn is_locked(owner: &AccountId, asset_id: &AssetId) -> bool;
+}
+impl<AccountId, AssetId> UniqueAssetLock<AccountId, AssetId> for () {
+	fn is_locked(_: &AccountId, _: &AssetId) -> bool { false }
+}
+
 /// This trait describes an abstraction over a set of unique assets, also known as non-fungible
 /// tokens (NFTs).
 ///
@@ -122,6 +276,8 @@ pub trait UniqueAssets<AccountId> {
 	type AssetLimit: Get<u128>;
 	/// The maximum number of this type of asset that any single account may own.
 	type UserAssetLimit: Get<u64>;
+	/// The type used to identify collections of unique assets.
+	type CollectionId: Encode + Decode + Member + Copy;
 
 	/// The total number of this type of asset that exists (minted - burned).
 	fn total() -> u128;
@@ -133,13 +289,28 @@ pub trait UniqueAssets<AccountId> {
 	fn assets_for_account(account: &AccountId) -> Vec<(Self::AssetId, Self::AssetInfo)>;
 	/// The ID of the account that owns an asset.
 	fn owner_of(asset_id: &Self::AssetId) -> AccountId;
+	/// Whether an asset is decayed and can not be used any more.
+	fn is_decayed(_asset_id: &Self::AssetId) -> bool { false }
+	/// The collection that an asset belongs to.
+	fn collection_of(asset_id: &Self::AssetId) -> Option<Self::CollectionId>;
+	/// The total number of assets that exists in a collection (minted - burned).
+	fn total_in_collection(collection_id: Self::CollectionId) -> u128;
+	/// The total number of assets in a collection owned by an account.
+	fn total_for_account_in_collection(collection_id: Self::CollectionId, account: &AccountId) -> u64;
+	/// The set of unique assets in a collection owned by an account.
+	fn assets_for_account_in_collection(
+	  collection_id: Self::CollectionId,
+	  account: &AccountId,
+	) -> Vec<(Self::AssetId, Self::AssetInfo)>;
 
-	/// Use the provided asset info to create a new unique asset for the specified user.
+	/// Use the provided asset info to create a new unique asset in a collection for the specified user.
 	/// This method **must** return an error in the following cases:
-	/// - The asset, as identified by the asset info, already exists.
-	/// - The specified owner account has already reached the user asset limit.
-	/// - The total asset limit has already been reached.
+	/// - The collection does not exist.
+	/// - The asset, as identified by the collection and asset info, already exists.
+	/// - The specified owner account has already reached the user asset limit of the type or the collection.
+	/// - The total asset limit of the type or the collection has already been reached.
 	fn mint(
+	  collection_id: Self::CollectionId,
 	  owner_account: &AccountId,
 	  asset_info: Self::AssetInfo,
 	) -> Result<Self::AssetId, DispatchError>;
@@ -150,6 +321,6 @@ pub trait UniqueAssets<AccountId> {
 	/// Transfer ownership of an asset to another account.
 	/// This method **must** return an error in the following cases:
 	/// - The asset with the specified ID does not exist.
-	/// - The destination account has already reached the user asset limit.
+	/// - The destination account has already reached the user asset limit of the type or the collection.
 	fn transfer(dest_account: &AccountId, asset_id: &Self::AssetId) -> DispatchResult;
 }
@@ -1,12 +1,30 @@
-use mc_support::traits::{ LifeTime };
-use super::primitives::{ BlockNumber };
+use sp_runtime::Percent;
+use mc_support::{
+	primitives::{ Formula, FeatureHue, FeatureRankedLevel, FeatureLevel },
+	traits::{ LifeTime, CraftingOdds, ExperienceCurve },
+};
+use super::primitives::{ BlockNumber, Balance };
 
 pub struct DemoActor;
 
+/// Blocks of a day with 6 seconds per block.
+const DAY_BLOCKS: BlockNumber = 14_400;
+
 impl LifeTime<BlockNumber> for DemoActor {
-	fn base_age(_: u32) -> BlockNumber {
-		// TODO
-		0
+	/// An actor is born with a week to live, every level up adds as many days as the level.
+	fn base_age(level: u32) -> BlockNumber {
+		if level <= 1 {
+			7 * DAY_BLOCKS
+		} else {
+			(level as BlockNumber).saturating_mul(DAY_BLOCKS)
+		}
+	}
+}
+
+impl ExperienceCurve for DemoActor {
+	/// Quadratic curve, 100 experience from level 1 and 400 from level 2.
+	fn required_experience(level: u32) -> u32 {
+		level.saturating_mul(level).saturating_mul(100)
 	}
 }
 
@@ -17,3 +35,133 @@ impl LifeTime<BlockNumber> for DemoItem {
 		10_000_000
 	}
 }
+
+/// The highest quality of an asset, `High(Lv5)` saturation plus `Lv5` lightness.
+const MAX_QUALITY: u32 = 17 + 5;
+
+/// Crafting odds weighted by the features of consumed assets.
+///
+/// - The purity of required hues scales the rate of formula from 50% up to 100%.
+/// - The average quality (saturation and lightness) of assets fills up to half of the
+///   remaining rate.
+pub struct FeatureWeightedOdds;
+
+impl FeatureWeightedOdds {
+	/// percent of the amounts which are in the required hues of formula
+	fn purity(formula: &Formula<u32, Balance>, feature_amounts: &[(FeatureHue, Balance)]) -> u32 {
+		let total = feature_amounts.iter()
+			.fold(0 as Balance, |acc, (_, amount)| acc.saturating_add(*amount));
+		if formula.minimum_elements.is_empty() || total == 0 {
+			return 100;
+		}
+		let required = feature_amounts.iter()
+			.filter(|(hue, _)| formula.minimum_elements.iter().any(|(one, _)| one == hue))
+			.fold(0 as Balance, |acc, (_, amount)| acc.saturating_add(*amount));
+		Percent::from_rational_approximation(required, total).deconstruct() as u32
+	}
+
+	/// percent of the average quality of consumed assets
+	fn quality(feature_levels: &[(FeatureRankedLevel, FeatureLevel)]) -> u32 {
+		if feature_levels.is_empty() {
+			return 0;
+		}
+		let total = feature_levels.iter().fold(0u32, |acc, (saturation, lightness)| {
+			let (rank, level) = match saturation.clone() {
+				FeatureRankedLevel::Low(level) => (0u32, level),
+				FeatureRankedLevel::Middle(level) => (6u32, level),
+				FeatureRankedLevel::High(level) => (12u32, level),
+			};
+			let level: u8 = level.into();
+			let lightness: u8 = lightness.clone().into();
+			acc + rank + level as u32 + lightness as u32
+		});
+		total * 100 / (MAX_QUALITY * feature_levels.len() as u32)
+	}
+}
+
+impl CraftingOdds<u32, Balance> for FeatureWeightedOdds {
+	fn success_rate(
+		formula: &Formula<u32, Balance>,
+		feature_amounts: &[(FeatureHue, Balance)],
+		feature_levels: &[(FeatureRankedLevel, FeatureLevel)],
+	) -> Percent {
+		let base = formula.rate_of_success.deconstruct() as u32;
+		let weighted = base * (100 + Self::purity(formula, feature_amounts)) / 200;
+		let rate = weighted + (100 - weighted) * Self::quality(feature_levels) / 200;
+		Percent::from_percent(rate as u8)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn formula(rate: u8, minimum_elements: Vec<(FeatureHue, Balance)>) -> Formula<u32, Balance> {
+		Formula {
+			rate_of_success: Percent::from_percent(rate),
+			minimum_elements,
+			..Default::default()
+		}
+	}
+
+	fn odds(
+		formula: &Formula<u32, Balance>,
+		feature_amounts: &[(FeatureHue, Balance)],
+		feature_levels: &[(FeatureRankedLevel, FeatureLevel)],
+	) -> Percent {
+		<FeatureWeightedOdds as CraftingOdds<u32, Balance>>::success_rate(formula, feature_amounts, feature_levels)
+	}
+
+	#[test]
+	fn lowest_quality_keeps_formula_rate() {
+		let formula = formula(50, vec![]);
+		assert_eq!(odds(&formula, &[], &[]), Percent::from_percent(50));
+		assert_eq!(
+			odds(&formula, &[(FeatureHue::Red, 10)], &[(FeatureRankedLevel::Low(FeatureLevel::Lv0), FeatureLevel::Lv0)]),
+			Percent::from_percent(50),
+		);
+	}
+
+	#[test]
+	fn odds_grow_with_quality() {
+		let formula = formula(50, vec![]);
+		let amounts = [(FeatureHue::Red, 10)];
+		let middle = odds(&formula, &amounts, &[(FeatureRankedLevel::Middle(FeatureLevel::Lv2), FeatureLevel::Lv3)]);
+		let high = odds(&formula, &amounts, &[(FeatureRankedLevel::High(FeatureLevel::Lv5), FeatureLevel::Lv5)]);
+		assert_eq!(middle, Percent::from_percent(62));
+		assert_eq!(high, Percent::from_percent(75));
+		// quality is averaged over all the assets
+		assert_eq!(odds(&formula, &amounts, &[
+			(FeatureRankedLevel::Low(FeatureLevel::Lv0), FeatureLevel::Lv0),
+			(FeatureRankedLevel::High(FeatureLevel::Lv5), FeatureLevel::Lv5),
+		]), middle);
+	}
+
+	#[test]
+	fn impure_elements_reduce_odds() {
+		let formula = formula(50, vec![(FeatureHue::Red, 1)]);
+		assert_eq!(odds(&formula, &[(FeatureHue::Red, 50)], &[]), Percent::from_percent(50));
+		assert_eq!(
+			odds(&formula, &[(FeatureHue::Red, 50), (FeatureHue::Blue, 50)], &[]),
+			Percent::from_percent(37),
+		);
+		assert_eq!(odds(&formula, &[(FeatureHue::Blue, 50)], &[]), Percent::from_percent(25));
+	}
+
+	#[test]
+	fn demo_actor_curve_grows() {
+		assert_eq!(DemoActor::required_experience(1), 100);
+		assert_eq!(DemoActor::required_experience(3), 900);
+		assert_eq!(DemoActor::base_age(1), 7 * DAY_BLOCKS);
+		assert_eq!(DemoActor::base_age(3), 3 * DAY_BLOCKS);
+	}
+
+	#[test]
+	fn odds_never_exceed_full_rate() {
+		let formula = formula(100, vec![]);
+		assert_eq!(
+			odds(&formula, &[], &[(FeatureRankedLevel::High(FeatureLevel::Lv5), FeatureLevel::Lv5)]),
+			Percent::from_percent(100),
+		);
+	}
+}
@@ -87,7 +87,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("mintcraft"),
 	impl_name: create_runtime_str!("mintcraft"),
 	authoring_version: 1,
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -232,6 +232,38 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+/// Root or an admin in the managers of Nature, the origin of game admin calls.
+type EnsureRootOrManager = frame_system::EnsureOneOf<
+	AccountId,
+	frame_system::EnsureRoot<AccountId>,
+	mc_nature::EnsureManager<Runtime>,
+>;
+
+/// Root or an asset issuer in the managers of Nature.
+type EnsureRootOrIssuer = frame_system::EnsureOneOf<
+	AccountId,
+	frame_system::EnsureRoot<AccountId>,
+	mc_nature::EnsureManager<Runtime, mc_support::traits::IssuerRole>,
+>;
+
+/// Root or an asset freezer in the managers of Nature.
+type EnsureRootOrFreezer = frame_system::EnsureOneOf<
+	AccountId,
+	frame_system::EnsureRoot<AccountId>,
+	mc_nature::EnsureManager<Runtime, mc_support::traits::FreezerRole>,
+>;
+
+/// Root or a formula designer in the managers of Nature.
+type EnsureRootOrDesigner = frame_system::EnsureOneOf<
+	AccountId,
+	frame_system::EnsureRoot<AccountId>,
+	mc_nature::EnsureManager<Runtime, mc_support::traits::DesignerRole>,
+>;
+
+/// The random source of game pallets, the secrets revealed by the servers of Nature.
+/// Use `Nature` instead to draw from `RandomnessCollectiveFlip` only.
+type GameRandomness = mc_nature::CommitReveal<Runtime>;
+
 // Assets Pallet
 parameter_types! {
 	pub const AssetDepositBase: Balance = 100 * DOLLARS;
@@ -246,6 +278,9 @@ impl mc_featured_assets::Config for Runtime {
 	type AssetId = u32;
 	type Currency = Balances;
 	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type ManagerOrigin = EnsureRootOrManager;
+	type IssuerOrigin = EnsureRootOrIssuer;
+	type FreezerOrigin = EnsureRootOrFreezer;
 	type AssetDepositBase = AssetDepositBase;
 	type AssetDepositPerZombie = AssetDepositPerZombie;
 	type StringLimit = StringLimit;
@@ -254,12 +289,20 @@ impl mc_featured_assets::Config for Runtime {
 	type WeightInfo = mc_featured_assets::weights::SubstrateWeight<Runtime>;
 	// Featured part
 	type AssetAdmin = Nature;
-	type RandomNumber = Nature;
+	type RandomNumber = GameRandomness;
 }
 
 parameter_types! {
-	pub const MaxNfts: u128 = 2^64;
+	pub const MaxNfts: u128 = 1 << 64;
 	pub const MaxNftsPerUser: u64 = 256;
+	pub const NftDecayPolicy: mc_nft::DecayPolicy = mc_nft::DecayPolicy::Burn;
+	pub const MaxDecaysPerBlock: u32 = 50;
+	pub const LifePerAsset: BlockNumber = 100;
+	pub const NftKeyLimit: u32 = 32;
+	pub const NftValueLimit: u32 = 256;
+	pub const NftMetadataLimit: u32 = 16;
+	/// The owner and issuer of the legacy collection of commodities minted before collections
+	pub LegacyCollectionOwner: AccountId = Sudo::key();
 }
 
 /// Configure all local pallets in ../pallets.
@@ -267,15 +310,42 @@ impl mc_nft::Config for Runtime {
 	type Event = Event;
 	type CommodityAdmin = frame_system::EnsureRoot<AccountId>;
 	type CommodityInfo = mc_support::primitives::UniqueAssetInfo<u32, BlockNumber>;
+	type OldCommodityInfo = mc_support::primitives::OldUniqueAssetInfo<u32, BlockNumber>;
 	type CommodityLimit = MaxNfts;
 	type UserCommodityLimit = MaxNftsPerUser;
 	type LifeTime = DemoItem;
+	type AssetLock = Actor;
+	type DecayPolicy = NftDecayPolicy;
+	type MaxDecaysPerBlock = MaxDecaysPerBlock;
+	type FeaturedAssets = FeaturedAssets;
+	type LifePerAsset = LifePerAsset;
+	type Currency = Balances;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type KeyLimit = NftKeyLimit;
+	type ValueLimit = NftValueLimit;
+	type MetadataLimit = NftMetadataLimit;
+	type LegacyCollectionOwner = LegacyCollectionOwner;
+	type WeightInfo = mc_nft::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const DeathEquipmentPolicy: mc_actor::EquipmentPolicy = mc_actor::EquipmentPolicy::Unequip;
+	pub const MaxActorLevel: u8 = 100;
+	/// The collection of crafted equipments, created at genesis, or by the runtime upgrade as the
+	/// legacy collection of the existing commodities
+	pub const EquipmentCollection: mc_nft::CollectionId = 0;
 }
 
 impl mc_actor::Config for Runtime {
 	type Event = Event;
 	type ActorLifeTime = DemoActor;
+	type FormulaId = u32;
 	type UniqueAssets = Commodity;
+	type DeathEquipmentPolicy = DeathEquipmentPolicy;
+	type ExperienceCurve = DemoActor;
+	type MaxLevel = MaxActorLevel;
+	type EquipmentCollection = EquipmentCollection;
 }
 
 impl mc_implication::Config for Runtime {
@@ -285,6 +355,10 @@ impl mc_implication::Config for Runtime {
 parameter_types! {
 	pub const NatureModuleId: ModuleId = ModuleId(*b"kis/stpr");
 	pub const MaxGenerateRandom: u32 = 10;
+	pub const ManagerDeposit: Balance = 100 * DOLLARS;
+	pub const SpendPeriod: BlockNumber = 14_400;
+	pub const RevealPeriod: BlockNumber = 100;
+	pub const RevealMissedSlash: Balance = 10 * DOLLARS;
 }
 
 impl mc_nature::Config for Runtime {
@@ -293,6 +367,11 @@ impl mc_nature::Config for Runtime {
 	type Balance = Balance;
 	type Currency = Balances;
 	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+	type ManagerDeposit = ManagerDeposit;
+	type FeaturedAssets = FeaturedAssets;
+	type SpendPeriod = SpendPeriod;
+	type RevealPeriod = RevealPeriod;
+	type RevealMissedSlash = RevealMissedSlash;
 	type Randomness = RandomnessCollectiveFlip;
 	type MaxGenerateRandom = MaxGenerateRandom;
 }
@@ -301,17 +380,29 @@ impl mc_nature::Config for Runtime {
 impl mc_cultivate::Config for Runtime {
 	type Event = Event;
 	type FormulaId = u32;
-	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
-	type RandomNumber = Nature;
+	type ManagerOrigin = EnsureRootOrDesigner;
+	type RandomNumber = GameRandomness;
+	type CraftingOdds = FeatureWeightedOdds;
 	type FormulaManager = Nature;
 	type FeaturedAssets = FeaturedAssets;
 	type UniqueAssets = Commodity;
+	type EquipmentCollection = EquipmentCollection;
 }
 
 parameter_types! {
 	pub const TicketClosingGap: u32 = 1_000;
 	pub const TicketPlayingGap: u32 = 1_000;
 	pub const AssetDistributionPercent: Percent = Percent::from_percent(90);
+	pub const ExpiredReportState: mc_support::primitives::DungeonReportState =
+		mc_support::primitives::DungeonReportState::Lose;
+	pub const TicketCancellationFee: Percent = Percent::from_percent(5);
+	pub const LeaderboardSize: u32 = 100;
+	pub const MaxPartySize: u32 = 5;
+	pub const ServerBond: Balance = 1_000 * DOLLARS;
+	pub const DisputePeriod: BlockNumber = 600;
+	pub const DisputeBond: Balance = 10 * DOLLARS;
+	pub const BaseExperience: u32 = 100;
+	pub const MaxExpiriesPerBlock: u32 = 50;
 }
 
 impl mc_dungeons::Config for Runtime {
@@ -319,14 +410,24 @@ impl mc_dungeons::Config for Runtime {
 	type DungeonId = u32;
 	type Balance = Balance;
 	type Currency = Balances;
-	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+	type ManagerOrigin = EnsureRootOrManager;
 	type AssetAdmin = Nature;
-	type RandomNumber = Nature;
-	type RandomHash = Nature;
+	type RandomNumber = GameRandomness;
+	type RandomHash = GameRandomness;
 	type FeaturedAssets = FeaturedAssets;
 	type TicketClosingGap = TicketClosingGap;
 	type TicketPlayingGap = TicketPlayingGap;
 	type AssetDistributionPercent = AssetDistributionPercent;
+	type ExpiredReportState = ExpiredReportState;
+	type TicketCancellationFee = TicketCancellationFee;
+	type LeaderboardSize = LeaderboardSize;
+	type MaxPartySize = MaxPartySize;
+	type ServerBond = ServerBond;
+	type DisputePeriod = DisputePeriod;
+	type DisputeBond = DisputeBond;
+	type Experience = Actor;
+	type BaseExperience = BaseExperience;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -346,12 +447,12 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the template pallet in the runtime.
 		FeaturedAssets: mc_featured_assets::{Module, Call, Storage, Event<T>},
-		Commodity: mc_nft::{Module, Call, Storage, Event<T>},
-		Actor: mc_actor::{Module, Call, Storage, Event<T>},
+		Commodity: mc_nft::{Module, Call, Storage, Config<T>, Event<T>},
+		Actor: mc_actor::{Module, Call, Storage, Config, Event<T>},
 		Implication: mc_implication::{Module, Call, Storage, Event<T>},
 		Cultivate: mc_cultivate::{Module, Call, Storage, Event<T>},
-		Nature: mc_nature::{Module, Call, Storage, Event<T>},
-		Dungeons: mc_dungeons::{Module, Call, Storage, Event<T>},
+		Nature: mc_nature::{Module, Call, Storage, Config, Event<T>},
+		Dungeons: mc_dungeons::{Module, Call, Storage, Config, Event<T>},
 	}
 );
 
@@ -520,6 +621,17 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl mc_dungeons_runtime_api::DungeonsApi<Block, AccountId, u32, Hash, BlockNumber, Balance> for Runtime {
+		fn player_runs(
+			who: AccountId,
+		) -> Vec<(Hash, mc_dungeons::DungeonInstance<u32, AccountId, BlockNumber, Balance>)> {
+			Dungeons::player_runs(&who)
+		}
+		fn leaderboard(id: u32) -> Vec<mc_dungeons::LeaderboardEntry<AccountId, BlockNumber, Hash>> {
+			Dungeons::leaderboards(id)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -554,6 +666,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+			add_benchmark!(params, batches, mc_nft, Commodity);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)